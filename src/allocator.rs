@@ -0,0 +1,113 @@
+use std::io;
+
+use super::Probe;
+
+/// A `no_std` crate using `alloc`, with a global allocator that never
+/// allocates, for the allocator probes to build on.
+const NO_STD_ALLOC: &'static str = "\
+#![no_std]
+extern crate alloc;
+use core::alloc::{GlobalAlloc, Layout};
+struct Null;
+unsafe impl GlobalAlloc for Null {
+    unsafe fn alloc(&self, _: Layout) -> *mut u8 { core::ptr::null_mut() }
+    unsafe fn dealloc(&self, _: *mut u8, _: Layout) { }
+}
+#[global_allocator]
+static ALLOCATOR: Null = Null;
+";
+
+impl Probe {
+    /// Probes whether a `no_std` crate can use the `alloc` crate with a
+    /// `#[global_allocator]` of its own on the target.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_global_allocator() );
+    /// ```
+    pub fn probe_global_allocator(&self) -> bool {
+        self.probe_items_as_lib(&format!("{}pub fn boxed() -> alloc::boxed::Box<u8> {{ \
+                                              alloc::boxed::Box::new(0) }}",
+                                         NO_STD_ALLOC))
+    }
+
+    /// Probes whether the unstable `Allocator` trait and allocator-generic
+    /// collections can be used, which needs a nightly compiler.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if probe.probe_allocator_api() {
+    ///     println!("cargo:rustc-cfg=allocator_api");
+    /// }
+    /// ```
+    pub fn probe_allocator_api(&self) -> bool {
+        self.probe_items_as_lib("#![no_std] #![feature(allocator_api)] extern crate alloc; \
+                                 pub fn boxed_in<A: core::alloc::Allocator>(a: A) \
+                                     -> alloc::boxed::Box<u8, A> { \
+                                     alloc::boxed::Box::new_in(0, a) }")
+    }
+
+    /// Determines whether a `no_std` binary or static library that uses
+    /// `alloc` must define an `#[alloc_error_handler]` on the target, as
+    /// with compilers before Rust 1.68, which had no default one.
+    ///
+    /// The probe builds a static library with a `#[panic_handler]` and
+    /// `-C panic=abort`, so that it fails only for want of an allocation
+    /// error handler.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with, or with
+    /// kind `InvalidData` if the probe fails for some other reason, such as
+    /// the target's lacking `alloc`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if probe.requires_alloc_error_handler().unwrap() {
+    ///     println!("cargo:rustc-cfg=needs_alloc_error_handler");
+    /// }
+    /// ```
+    pub fn requires_alloc_error_handler(&self) -> io::Result<bool> {
+        let mut probe = self.clone();
+        probe.crate_type = Some("staticlib");
+        probe.arg("-C").arg("panic=abort");
+
+        let code = format!("{}#[panic_handler] \
+                            fn panic(_: &core::panic::PanicInfo) -> ! {{ loop {{ }} }} \
+                            #[no_mangle] pub extern \"C\" fn len() -> usize {{ \
+                                alloc::vec![1u8].len() }}",
+                           NO_STD_ALLOC);
+        let mut command = String::new();
+        let mut diagnostics = String::new();
+
+        if probe.compile_capturing(&code, &mut command, &mut diagnostics)? {
+            Ok(false)
+        } else if diagnostics.contains("alloc_error_handler") {
+            Ok(true)
+        } else {
+            let error = diagnostics.lines().find(|line| line.starts_with("error")).unwrap_or("");
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               format!("no_std allocation probe failed: {}", error)))
+        }
+    }
+}
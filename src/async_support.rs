@@ -0,0 +1,51 @@
+use super::{Probe, SyntaxFeature};
+
+/// Which async language features the compiler supports, from
+/// [`Probe::async_support`](struct.Probe.html#method.async_support).
+///
+/// Each field is the answer to the [`SyntaxFeature`](enum.SyntaxFeature.html)
+/// probe of the same name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AsyncSupport {
+    /// `async fn` declarations.
+    pub async_fn:           bool,
+    /// `async { ... }` blocks.
+    pub async_blocks:       bool,
+    /// Async closures such as `async || { ... }`.
+    pub async_closures:     bool,
+    /// `async fn` in traits and their impls.
+    pub async_fn_in_trait:  bool,
+    /// Functions returning `impl Future<Output = T>`.
+    pub impl_future_return: bool,
+}
+
+impl Probe {
+    /// Probes for each of the async language features, summarized as an
+    /// [`AsyncSupport`](struct.AsyncSupport.html).
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let support = Probe::new().async_support();
+    /// if support.async_fn_in_trait {
+    ///     println!("cargo:rustc-cfg=async_trait_native");
+    /// }
+    /// assert!( ! support.async_fn_in_trait || support.async_fn );
+    /// ```
+    pub fn async_support(&self) -> AsyncSupport {
+        AsyncSupport {
+            async_fn:           self.probe_syntax(SyntaxFeature::AsyncFn),
+            async_blocks:       self.probe_syntax(SyntaxFeature::AsyncBlocks),
+            async_closures:     self.probe_syntax(SyntaxFeature::AsyncClosures),
+            async_fn_in_trait:  self.probe_syntax(SyntaxFeature::AsyncFnInTrait),
+            impl_future_return: self.probe_syntax(SyntaxFeature::ImplFutureReturn),
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cancel::{self, CancellationToken};
+use compat;
+use limits::ResourceLimits;
+use text;
+
+/// The step of probing that decides whether a probe program compiles.
+///
+/// A [`Probe`](struct.Probe.html) renders each probe program and prepares the
+/// `rustc` command to compile it, and then hands both to its backend as a
+/// [`Compilation`](struct.Compilation.html). The default backend,
+/// [`RustcBackend`](struct.RustcBackend.html), runs the command. Substituting
+/// [`ScriptedBackend`](struct.ScriptedBackend.html) (or your own
+/// implementation) with [`Probe::backend`](struct.Probe.html#method.backend)
+/// makes it possible to test build-script logic without a compiler, or to run
+/// compilations some other way.
+pub trait ProbeBackend: Send + Sync {
+    /// Carries out the compilation, returning whether it succeeded.
+    fn compile(&self, compilation: &mut Compilation) -> io::Result<bool>;
+}
+
+/// A probe program to compile, along with the prepared `rustc` command and
+/// the settings that govern running it.
+#[derive(Debug)]
+pub struct Compilation<'a> {
+    command:     Command,
+    code:        &'a str,
+    timeout:     Option<Duration>,
+    limits:      ResourceLimits,
+    cancel:      Option<CancellationToken>,
+    diagnostics: String,
+}
+
+/// Prepares a compilation of the program with the command and settings.
+pub fn compilation<'a>(command: Command, code: &'a str, timeout: Option<Duration>,
+                       limits: ResourceLimits, cancel: Option<CancellationToken>)
+                       -> Compilation<'a> {
+    Compilation {
+        command:     command,
+        code:        code,
+        timeout:     timeout,
+        limits:      limits,
+        cancel:      cancel,
+        diagnostics: String::new(),
+    }
+}
+
+impl<'a> Compilation<'a> {
+    /// The prepared `rustc` command, which expects the program on its piped
+    /// standard input, unless the compiler reads it from a file.
+    pub fn command(&mut self) -> &mut Command {
+        &mut self.command
+    }
+
+    /// The probe program.
+    pub fn code(&self) -> &str {
+        self.code
+    }
+
+    /// How long to let the compiler run before giving up, if limited.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// The token that abandons the compilation, if any. Backends that
+    /// compile some other way should stop when it is cancelled.
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancel.as_ref()
+    }
+
+    /// The compiler's diagnostics, captured from its standard error by
+    /// [`run`](#method.run). Output that isn't valid UTF-8, as from a linker
+    /// writing in a Windows code page, is converted lossily. Backends that compile some other way can set
+    /// them with [`set_diagnostics`](#method.set_diagnostics).
+    pub fn diagnostics(&self) -> &str {
+        &self.diagnostics
+    }
+
+    /// Sets the compiler's diagnostics.
+    pub fn set_diagnostics<S: Into<String>>(&mut self, diagnostics: S) {
+        self.diagnostics = diagnostics.into();
+    }
+
+    /// Runs the command, feeding it the program and enforcing the timeout,
+    /// resource limits and cancellation.
+    ///
+    /// The compiler's standard error is captured as the
+    /// [`diagnostics`](#method.diagnostics) and then passed on to this
+    /// process's standard error.
+    ///
+    /// This is what [`RustcBackend`](struct.RustcBackend.html) does, and
+    /// other backends can delegate to it after adjusting the command.
+    ///
+    /// # Errors
+    ///
+    /// If the command cannot be started or communicated with, if it runs
+    /// past the timeout (`ErrorKind::TimedOut`), if it is terminated by a
+    /// signal or stopped for exceeding a resource limit, or if it is
+    /// cancelled (`ErrorKind::Interrupted`).
+    pub fn run(&mut self) -> io::Result<bool> {
+        if let Some(ref cancel) = self.cancel {
+            cancel::check(cancel)?;
+        }
+        self.limits.apply_before_spawn(&mut self.command)?;
+        let mut child = self.command.stderr(Stdio::piped()).spawn()?;
+
+        let guard = match self.limits.apply_after_spawn(&child) {
+            Ok(guard) => guard,
+            Err(e)    => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+        };
+
+        // Read on another thread so that the compiler can't fill the pipe
+        // and block while we feed it or wait for it.
+        let mut pipe = child.stderr.take().unwrap();
+        // A read error keeps what was read before it, since diagnostics
+        // only inform the answer, which comes from the exit status.
+        let reader = thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = pipe.read_to_end(&mut bytes);
+            bytes
+        });
+
+        // Compilers that read the program from a file have no piped input.
+        // One that exits without reading it, such as on a bad argument, is
+        // judged by its exit status.
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(self.code.as_bytes()) {
+                Ok(())                                              => (),
+                Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => (),
+                Err(e)                                              => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    let _ = reader.join();
+                    return Err(e);
+                }
+            }
+        }
+
+        let status = wait_timeout(&mut child, self.timeout, self.cancel.as_ref(), "rustc")?;
+
+        if let Ok(bytes) = reader.join() {
+            let _ = io::stderr().write_all(&bytes);
+            self.diagnostics = text::decode(&bytes);
+        }
+
+        if status.code().is_none() || guard.exceeded(&status) {
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      format!("rustc was terminated: {}", status)));
+        }
+
+        Ok(status.success())
+    }
+}
+
+/// Waits for the child to exit, killing it if it runs past the timeout or is
+/// cancelled.
+pub fn wait_timeout(child: &mut Child, timeout: Option<Duration>,
+                    cancel: Option<&CancellationToken>, what: &str)
+                    -> io::Result<ExitStatus> {
+    if timeout.is_none() && cancel.is_none() {
+        return child.wait();
+    }
+
+    let start = Instant::now();
+    loop {
+        if let Some(status) = compat::try_wait(child)? {
+            return Ok(status);
+        }
+        if let Some(Err(e)) = cancel.map(cancel::check) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+        if timeout.map_or(false, |timeout| start.elapsed() >= timeout) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                      format!("{} timed out", what)));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// The default backend, which runs `rustc`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RustcBackend;
+
+impl ProbeBackend for RustcBackend {
+    fn compile(&self, compilation: &mut Compilation) -> io::Result<bool> {
+        compilation.run()
+    }
+}
+
+/// A backend that answers from a script instead of running a compiler.
+///
+/// Each rule pairs a pattern with an answer. A probe program gets the answer
+/// of the first rule whose pattern it contains, or the default answer if no
+/// rule matches. Without a default answer, unmatched programs are an error,
+/// which makes unexpected probes easy to spot in tests.
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::{Probe, ScriptedBackend};
+///
+/// let mut probe = Probe::new();
+/// probe.backend(ScriptedBackend::new()
+///                   .answer("i128", true)
+///                   .answer("u512", false));
+///
+/// assert!(   probe.probe_type("i128") );
+/// assert!( ! probe.probe_type("u512") );
+/// assert!(   probe.probe_result("fn main() { }").is_err() );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScriptedBackend {
+    rules:   Vec<(String, bool)>,
+    default: Option<bool>,
+}
+
+impl ScriptedBackend {
+    /// Creates a backend with no rules and no default answer.
+    pub fn new() -> Self {
+        ScriptedBackend {
+            rules:   Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Adds a rule answering `present` for programs containing `pattern`.
+    pub fn answer<S: Into<String>>(mut self, pattern: S, present: bool) -> Self {
+        self.rules.push((pattern.into(), present));
+        self
+    }
+
+    /// Sets the answer for programs that match no rule.
+    pub fn default_answer(mut self, present: bool) -> Self {
+        self.default = Some(present);
+        self
+    }
+}
+
+impl ProbeBackend for ScriptedBackend {
+    fn compile(&self, compilation: &mut Compilation) -> io::Result<bool> {
+        let code = compilation.code();
+        self.rules.iter()
+            .find(|rule| code.contains(rule.0.as_str()))
+            .map(|rule| rule.1)
+            .or(self.default)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound,
+                                          format!("no scripted answer for: {}", code)))
+    }
+}
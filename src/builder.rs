@@ -0,0 +1,289 @@
+use std::ffi::OsString;
+use std::io;
+use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{BuildContext, CancellationToken, Probe, ProbeBackend, ProbeEvent, Progress,
+            Reporter, ToolchainPolicy, UnavailablePolicy};
+
+/// A consuming builder for [`Probe`](struct.Probe.html).
+///
+/// Each method here does the same as the `Probe` method of the same name,
+/// but takes and returns the builder by value, so that a probe can be
+/// configured in a single expression. Create one with
+/// [`Probe::builder`](struct.Probe.html#method.builder).
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::Probe;
+///
+/// let probe = Probe::builder()
+///     .edition("2018")
+///     .arg("--cfg").arg("probing")
+///     .build();
+/// assert!( probe.probe_expression("{ #[cfg(probing)] let r#try = 1; r#try }") );
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProbeBuilder {
+    probe: Probe,
+}
+
+impl ProbeBuilder {
+    /// Starts from the configuration of [`Probe::new`](struct.Probe.html#method.new).
+    pub fn new() -> Self {
+        ProbeBuilder { probe: Probe::new() }
+    }
+
+    /// Finishes building.
+    pub fn build(self) -> Probe {
+        self.probe
+    }
+
+    /// See [`Probe::wrapper_command`](struct.Probe.html#method.wrapper_command).
+    pub fn wrapper_command<I>(mut self, wrapper: I) -> Self
+        where I: IntoIterator,
+              I::Item: Into<OsString>
+    {
+        self.probe.wrapper_command(wrapper);
+        self
+    }
+
+    /// See [`Probe::toolchain`](struct.Probe.html#method.toolchain).
+    ///
+    /// # Errors
+    ///
+    /// If rustup cannot be run or the toolchain isn't installed.
+    pub fn toolchain(mut self, toolchain: &str) -> io::Result<Self> {
+        self.probe.toolchain(toolchain)?;
+        Ok(self)
+    }
+
+    /// See [`Probe::target`](struct.Probe.html#method.target).
+    pub fn target<S: Into<OsString>>(mut self, target: S) -> Self {
+        self.probe.target(target);
+        self
+    }
+
+    /// See [`Probe::edition`](struct.Probe.html#method.edition).
+    pub fn edition<S: Into<String>>(mut self, edition: S) -> Self {
+        self.probe.edition(edition);
+        self
+    }
+
+    /// See [`Probe::emit`](struct.Probe.html#method.emit).
+    ///
+    /// # Errors
+    ///
+    /// If the list is empty or names a kind `rustc` doesn't know.
+    pub fn emit<S: Into<String>>(mut self, kinds: S) -> io::Result<Self> {
+        self.probe.emit(kinds)?;
+        Ok(self)
+    }
+
+    /// See [`Probe::cfg_prefix`](struct.Probe.html#method.cfg_prefix).
+    pub fn cfg_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.probe.cfg_prefix(prefix);
+        self
+    }
+
+    /// See [`Probe::hardened`](struct.Probe.html#method.hardened).
+    pub fn hardened(mut self, hardened: bool) -> Self {
+        self.probe.hardened(hardened);
+        self
+    }
+
+    /// See [`Probe::sanitize_env`](struct.Probe.html#method.sanitize_env).
+    pub fn sanitize_env(mut self, sanitize: bool) -> Self {
+        self.probe.sanitize_env(sanitize);
+        self
+    }
+
+    /// See [`Probe::allow_env`](struct.Probe.html#method.allow_env).
+    pub fn allow_env<S: Into<OsString>>(mut self, var: S) -> Self {
+        self.probe.allow_env(var);
+        self
+    }
+
+    /// See [`Probe::arg`](struct.Probe.html#method.arg).
+    pub fn arg<S: Into<OsString>>(mut self, arg: S) -> Self {
+        self.probe.arg(arg);
+        self
+    }
+
+    /// See [`Probe::args`](struct.Probe.html#method.args).
+    pub fn args<I>(mut self, args: I) -> Self
+        where I: IntoIterator,
+              I::Item: Into<OsString>
+    {
+        self.probe.args(args);
+        self
+    }
+
+    /// See [`Probe::cfg`](struct.Probe.html#method.cfg).
+    pub fn cfg<S: AsRef<str>>(mut self, spec: S) -> Self {
+        self.probe.cfg(spec);
+        self
+    }
+
+    /// See [`Probe::cfg_value`](struct.Probe.html#method.cfg_value).
+    pub fn cfg_value<N, V>(mut self, name: N, value: V) -> Self
+        where N: AsRef<str>,
+              V: AsRef<str>
+    {
+        self.probe.cfg_value(name, value);
+        self
+    }
+
+    /// See [`Probe::check_cfg`](struct.Probe.html#method.check_cfg).
+    pub fn check_cfg<S: AsRef<str>>(mut self, spec: S) -> Self {
+        self.probe.check_cfg(spec);
+        self
+    }
+
+    /// See [`Probe::env`](struct.Probe.html#method.env).
+    pub fn env<K, V>(mut self, key: K, value: V) -> Self
+        where K: Into<OsString>,
+              V: Into<OsString>
+    {
+        self.probe.env(key, value);
+        self
+    }
+
+    /// See [`Probe::timeout`](struct.Probe.html#method.timeout).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.probe.timeout(timeout);
+        self
+    }
+
+    /// See [`Probe::cpu_time_limit`](struct.Probe.html#method.cpu_time_limit).
+    #[cfg(feature = "resource-limits")]
+    pub fn cpu_time_limit(mut self, limit: Duration) -> Self {
+        self.probe.cpu_time_limit(limit);
+        self
+    }
+
+    /// See [`Probe::memory_limit`](struct.Probe.html#method.memory_limit).
+    #[cfg(feature = "resource-limits")]
+    pub fn memory_limit(mut self, bytes: u64) -> Self {
+        self.probe.memory_limit(bytes);
+        self
+    }
+
+    /// See [`Probe::low_priority`](struct.Probe.html#method.low_priority).
+    #[cfg(feature = "resource-limits")]
+    pub fn low_priority(mut self, low: bool) -> Self {
+        self.probe.low_priority(low);
+        self
+    }
+
+    /// See [`Probe::cancellation`](struct.Probe.html#method.cancellation).
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.probe.cancellation(token);
+        self
+    }
+
+    /// See [`Probe::progress`](struct.Probe.html#method.progress).
+    pub fn progress(mut self, output: Progress) -> Self {
+        self.probe.progress(output);
+        self
+    }
+
+    /// See [`Probe::runner`](struct.Probe.html#method.runner).
+    pub fn runner<I>(mut self, runner: I) -> Self
+        where I: IntoIterator,
+              I::Item: Into<OsString>
+    {
+        self.probe.runner(runner);
+        self
+    }
+
+    /// See [`Probe::build_context`](struct.Probe.html#method.build_context).
+    pub fn build_context(mut self, context: BuildContext) -> Self {
+        self.probe.build_context(context);
+        self
+    }
+
+    /// See [`Probe::answer_in`](struct.Probe.html#method.answer_in).
+    pub fn answer_in(mut self, context: BuildContext, present: bool) -> Self {
+        self.probe.answer_in(context, present);
+        self
+    }
+
+    /// See [`Probe::on_probe_unavailable`](struct.Probe.html#method.on_probe_unavailable).
+    pub fn on_probe_unavailable(mut self, policy: UnavailablePolicy) -> Self {
+        self.probe.on_probe_unavailable(policy);
+        self
+    }
+
+    /// See [`Probe::on_toolchain_mismatch`](struct.Probe.html#method.on_toolchain_mismatch).
+    pub fn on_toolchain_mismatch(mut self, policy: ToolchainPolicy) -> Self {
+        self.probe.on_toolchain_mismatch(policy);
+        self
+    }
+
+    /// See [`Probe::cache_file`](struct.Probe.html#method.cache_file).
+    pub fn cache_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.probe.cache_file(path);
+        self
+    }
+
+    /// See [`Probe::cache_dir`](struct.Probe.html#method.cache_dir).
+    pub fn cache_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.probe.cache_dir(dir);
+        self
+    }
+
+    /// See [`Probe::auto_self_check`](struct.Probe.html#method.auto_self_check).
+    pub fn auto_self_check(mut self, check: bool) -> Self {
+        self.probe.auto_self_check(check);
+        self
+    }
+
+    /// See [`Probe::share_answers`](struct.Probe.html#method.share_answers).
+    pub fn share_answers(mut self, share: bool) -> Self {
+        self.probe.share_answers(share);
+        self
+    }
+
+    /// See [`Probe::backend`](struct.Probe.html#method.backend).
+    pub fn backend<B: ProbeBackend + 'static>(mut self, backend: B) -> Self {
+        self.probe.backend(backend);
+        self
+    }
+
+    /// See [`Probe::before_spawn`](struct.Probe.html#method.before_spawn).
+    pub fn before_spawn<F>(mut self, hook: F) -> Self
+        where F: FnMut(&mut Command) + Send + 'static
+    {
+        self.probe.before_spawn(hook);
+        self
+    }
+
+    /// See [`Probe::observer`](struct.Probe.html#method.observer).
+    pub fn observer<F>(mut self, observer: F) -> Self
+        where F: Fn(&ProbeEvent) + Send + Sync + 'static
+    {
+        self.probe.observer(observer);
+        self
+    }
+
+    /// See [`Probe::reporter`](struct.Probe.html#method.reporter).
+    pub fn reporter<R: Reporter + 'static>(mut self, reporter: R) -> Self {
+        self.probe.reporter(reporter);
+        self
+    }
+}
+
+impl Default for ProbeBuilder {
+    fn default() -> Self {
+        ProbeBuilder::new()
+    }
+}
+
+impl From<ProbeBuilder> for Probe {
+    fn from(builder: ProbeBuilder) -> Self {
+        builder.build()
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use temp;
+
+/// A summary of the answers in a probe's cache file, from
+/// [`Probe::cache_stats`](struct.Probe.html#method.cache_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CacheStats {
+    /// The number of cached answers.
+    pub entries: usize,
+    /// How many of them are positive.
+    pub present: usize,
+    /// How many of them are negative.
+    pub absent:  usize,
+}
+
+/// Reads the answers recorded in a cache file, where each line is a key as
+/// 16 hexadecimal digits, a space, and `1` or `0`. A missing file has no
+/// answers, and malformed lines are skipped.
+pub fn load(path: &Path) -> io::Result<BTreeMap<u64, bool>> {
+    read(path).map(|(answers, _)| answers)
+}
+
+/// Counts the malformed lines in a cache file, which suggest that it was
+/// corrupted or written by something else.
+pub fn malformed_lines(path: &Path) -> io::Result<usize> {
+    read(path).map(|(_, malformed)| malformed)
+}
+
+fn read(path: &Path) -> io::Result<(BTreeMap<u64, bool>, usize)> {
+    let mut contents = String::new();
+    match File::open(path) {
+        Ok(mut file)                                     => { file.read_to_string(&mut contents)?; }
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok((BTreeMap::new(), 0)),
+        Err(e)                                            => return Err(e),
+    }
+
+    let mut answers = BTreeMap::new();
+    let mut malformed = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let mut words = line.split_whitespace();
+        let entry = match (words.next(), words.next(), words.next()) {
+            (Some(key), Some("1"), None) => u64::from_str_radix(key, 16).ok().map(|key| (key, true)),
+            (Some(key), Some("0"), None) => u64::from_str_radix(key, 16).ok().map(|key| (key, false)),
+            _                            => None,
+        };
+        match entry {
+            Some((key, present)) => { answers.insert(key, present); }
+            None                 => malformed += 1,
+        }
+    }
+
+    Ok((answers, malformed))
+}
+
+/// Records an answer in a cache file, replacing the file atomically so that
+/// concurrent readers never see it half written.
+pub fn store(path: &Path, key: u64, present: bool) -> io::Result<()> {
+    let mut answers = load(path)?;
+    if answers.get(&key) == Some(&present) {
+        return Ok(());
+    }
+    answers.insert(key, present);
+
+    let mut contents = String::new();
+    for (key, present) in &answers {
+        contents.push_str(&format!("{:016x} {}\n", key, if *present { 1 } else { 0 }));
+    }
+
+    // Clones sharing the file may store from several threads at once.
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(format!(".{}.tmp", temp::unique_name("store")));
+    File::create(&temp_path)?.write_all(contents.as_bytes())?;
+    fs::rename(&temp_path, path)
+}
@@ -0,0 +1,70 @@
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A flag for abandoning probes from another thread, such as when the user
+/// of an IDE or other embedding tool stops waiting for them.
+///
+/// Give a clone to a probe with
+/// [`Probe::cancellation`](struct.Probe.html#method.cancellation). Once
+/// [`cancel`](#method.cancel) is called, a running compiler is killed and
+/// no more are started: each probe fails with an error of kind
+/// `Interrupted`, whatever the probe's
+/// [`UnavailablePolicy`](enum.UnavailablePolicy.html), so that loops over
+/// many probes stop at the next one. Clones share the flag.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use feature_probe::{CancellationToken, Probe};
+///
+/// let token = CancellationToken::new();
+/// let mut probe = Probe::new();
+/// probe.cancellation(token.clone());
+///
+/// token.cancel();
+/// let error = probe.probe_result("fn main() { }").unwrap_err();
+/// assert_eq!( error.kind(), io::ErrorKind::Interrupted );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    parent:    Option<Arc<AtomicBool>>,
+}
+
+impl CancellationToken {
+    /// Creates a token that hasn't been cancelled.
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Cancels the probes holding this token or its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether [`cancel`](#method.cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self.parent.as_ref().map_or(false, |parent| parent.load(Ordering::SeqCst))
+    }
+}
+
+/// A token of its own, which is also cancelled when the given one is.
+#[cfg(feature = "async")]
+pub fn child(parent: Option<&CancellationToken>) -> CancellationToken {
+    CancellationToken {
+        cancelled: Arc::new(AtomicBool::new(false)),
+        parent:    parent.map(|parent| parent.cancelled.clone()),
+    }
+}
+
+/// Fails if the token has been cancelled.
+pub fn check(token: &CancellationToken) -> io::Result<()> {
+    if token.is_cancelled() {
+        Err(io::Error::new(io::ErrorKind::Interrupted, "probe cancelled"))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use std::env;
+use std::io;
+
+use super::{compat, version, Probe, Version};
+
+/// A code generation backend of the Rust compiler.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CodegenBackend {
+    /// The default backend, LLVM.
+    Llvm,
+    /// The Cranelift backend, `rustc_codegen_cranelift`.
+    Cranelift,
+    /// The GCC backend, `rustc_codegen_gcc`.
+    Gcc,
+    /// Some other backend, by the name or path it was selected with.
+    Other(String),
+}
+
+impl CodegenBackend {
+    /// Interprets the value of `-Z codegen-backend`.
+    fn from_flag(value: &str) -> Self {
+        if value == "llvm" {
+            CodegenBackend::Llvm
+        } else if value.contains("cranelift") {
+            CodegenBackend::Cranelift
+        } else if value == "gcc" || value.contains("codegen_gcc") {
+            CodegenBackend::Gcc
+        } else {
+            CodegenBackend::Other(value.to_owned())
+        }
+    }
+}
+
+impl Probe {
+    /// Determines the version of LLVM the compiler was built with, or `None`
+    /// if it doesn't say, as compilers built without LLVM don't.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc -vV` cannot be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, Version};
+    ///
+    /// let probe = Probe::new();
+    /// if let Some(llvm) = probe.llvm_version().unwrap() {
+    ///     assert!( llvm >= Version::new(6, 0, 0) );
+    /// }
+    /// ```
+    pub fn llvm_version(&self) -> io::Result<Option<Version>> {
+        let verbose = self.rustc_verbose_version()?;
+        Ok(version::verbose_field(&verbose, "LLVM version").and_then(Version::parse))
+    }
+
+    /// Determines the code generation backend probes are compiled with.
+    ///
+    /// This is the backend selected with `-Z codegen-backend`, in the probe's
+    /// arguments or in the flags Cargo passes to the crate being built
+    /// (`CARGO_ENCODED_RUSTFLAGS`), and otherwise LLVM if the compiler was
+    /// built with it.
+    ///
+    /// # Errors
+    ///
+    /// If no backend is selected and `rustc -vV` cannot be run or doesn't
+    /// mention LLVM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{CodegenBackend, Probe};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.args(&["-Z", "codegen-backend=cranelift"]);
+    /// assert_eq!( probe.codegen_backend().unwrap(), CodegenBackend::Cranelift );
+    /// ```
+    pub fn codegen_backend(&self) -> io::Result<CodegenBackend> {
+        let probe_flags = self.args.iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        let cargo_flags = env::var("CARGO_ENCODED_RUSTFLAGS")
+            .map(|flags| flags.split('\x1f').map(str::to_owned).collect())
+            .unwrap_or_else(|_| Vec::new());
+
+        // The probe's own flags come last, so that they win.
+        if let Some(value) = selected_backend(cargo_flags.iter().chain(&probe_flags)) {
+            return Ok(CodegenBackend::from_flag(&value));
+        }
+
+        if self.llvm_version()?.is_some() {
+            Ok(CodegenBackend::Llvm)
+        } else {
+            Err(io::Error::new(io::ErrorKind::Other,
+                               "cannot determine the codegen backend"))
+        }
+    }
+
+    /// Probes whether a `-C` flag and its value are accepted for the
+    /// probe's target, so that flags such as `-C split-debuginfo=unpacked`
+    /// can be forwarded only to compilers that understand them.
+    ///
+    /// The flag may be given as `-Cname=value`, `-C name=value` or just
+    /// `name=value`. A flag that `rustc` accepts only with a warning, such as
+    /// an unknown `target-cpu`, counts as not accepted.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_codegen_flag("-Copt-level=2") );
+    /// assert!(   probe.probe_codegen_flag("-C debuginfo=1") );
+    /// assert!( ! probe.probe_codegen_flag("-Copt-level=9") );
+    /// assert!( ! probe.probe_codegen_flag("-Cno-such-flag=1") );
+    /// assert!( ! probe.probe_codegen_flag("target-cpu=no-such-cpu") );
+    /// ```
+    pub fn probe_codegen_flag(&self, flag: &str) -> bool {
+        let option = if flag.starts_with("-C") { compat::trim_start(&flag[2..]) } else { flag };
+        self.accepts_flags(&["-C", option]).expect("Probe::probe_codegen_flag")
+    }
+
+    /// Probes whether the named sanitizer, such as `"address"`, works for
+    /// the probe's target, so that sanitizer-specific code paths can be
+    /// enabled only where they can be used.
+    ///
+    /// The sanitizer is tried with `-C sanitize=` and then with the unstable
+    /// `-Z sanitizer=`, which only nightly compilers accept. The probe is
+    /// linked, so a missing sanitizer runtime counts as unsupported. So do
+    /// sanitizers such as `thread` and `memory` that need the standard
+    /// library rebuilt with them, unless the probe's arguments allow the ABI
+    /// mismatch.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if probe.probe_sanitizer("address") {
+    ///     println!("cargo:rustc-cfg=asan_available");
+    /// }
+    /// assert!( ! probe.probe_sanitizer("no-such-sanitizer") );
+    /// ```
+    pub fn probe_sanitizer(&self, sanitizer: &str) -> bool {
+        let mut probe = self.clone();
+        probe.emit = String::from("link");
+        probe.crate_type = None;
+
+        let stable = format!("sanitize={}", sanitizer);
+        let unstable = format!("sanitizer={}", sanitizer);
+        probe.accepts_flags(&["-C", &stable])
+            .and_then(|accepted| if accepted {
+                Ok(true)
+            } else {
+                probe.accepts_flags(&["-Z", &unstable])
+            })
+            .expect("Probe::probe_sanitizer")
+    }
+}
+
+/// Finds the last `-Z codegen-backend=…` among the flags, in any of the
+/// spellings `rustc` accepts.
+fn selected_backend<'a, I>(flags: I) -> Option<String>
+    where I: Iterator<Item = &'a String>
+{
+    let prefix = "codegen-backend=";
+    let mut flags = flags.map(String::as_str);
+    let mut selected = None;
+
+    while let Some(flag) = flags.next() {
+        let option = if flag == "-Z" {
+            flags.next().unwrap_or("")
+        } else if flag.starts_with("-Z") {
+            &flag[2..]
+        } else {
+            continue;
+        };
+
+        if option.starts_with(prefix) {
+            selected = Some(option[prefix.len()..].to_owned());
+        }
+    }
+
+    selected
+}
@@ -0,0 +1,161 @@
+//! Stand-ins for standard library functions newer than the minimum
+//! supported Rust version, 1.16.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Child, ExitStatus};
+use std::time::Duration;
+
+/// Reads a whole file as text, like `fs::read_to_string` (Rust 1.26).
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Reads a whole file, like `fs::read` (Rust 1.26).
+pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    File::open(path)?.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+/// Replaces a file's contents, like `fs::write` (Rust 1.26).
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    File::create(path)?.write_all(contents.as_ref())
+}
+
+/// The path and its ancestors, nearest first, like `Path::ancestors`
+/// (Rust 1.28).
+pub fn ancestors<'a>(path: &'a Path) -> Ancestors<'a> {
+    Ancestors { next: Some(path) }
+}
+
+pub struct Ancestors<'a> {
+    next: Option<&'a Path>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = &'a Path;
+
+    fn next(&mut self) -> Option<&'a Path> {
+        let next = self.next;
+        self.next = next.and_then(Path::parent);
+        next
+    }
+}
+
+/// The string without leading whitespace, like `str::trim_start` (Rust
+/// 1.30), which replaces the deprecated `trim_left`.
+pub fn trim_start(s: &str) -> &str {
+    match s.find(|c: char| !c.is_whitespace()) {
+        Some(start) => &s[start..],
+        None        => "",
+    }
+}
+
+/// The bytes of a number in little-endian order, like `u64::to_le_bytes`
+/// (Rust 1.32).
+pub fn le_bytes(n: u64) -> [u8; 8] {
+    let mut bytes = [0; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = (n >> (8 * i)) as u8;
+    }
+    bytes
+}
+
+/// The milliseconds past the whole second, like `Duration::subsec_millis`
+/// (Rust 1.27).
+pub fn subsec_millis(duration: Duration) -> u32 {
+    let nanos = duration.subsec_nanos();
+    nanos / 1_000_000
+}
+
+/// The child's exit status if it has exited, without waiting, like
+/// `Child::try_wait` (Rust 1.18). Once this returns a status, the child has
+/// been reaped, so it must not be waited for again.
+#[cfg(unix)]
+pub fn try_wait(child: &mut Child) -> io::Result<Option<ExitStatus>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    extern "C" {
+        fn waitpid(pid: i32, status: *mut i32, options: i32) -> i32;
+    }
+    const WNOHANG: i32 = 1;
+
+    let mut status = 0;
+    match unsafe { waitpid(child.id() as i32, &mut status, WNOHANG) } {
+        0  => Ok(None),
+        -1 => {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted { Ok(None) } else { Err(error) }
+        }
+        _  => Ok(Some(ExitStatus::from_raw(status))),
+    }
+}
+
+/// The child's exit status if it has exited, without waiting, like
+/// `Child::try_wait` (Rust 1.18).
+#[cfg(windows)]
+pub fn try_wait(child: &mut Child) -> io::Result<Option<ExitStatus>> {
+    use std::os::raw::c_void;
+    use std::os::windows::io::AsRawHandle;
+    use std::os::windows::process::ExitStatusExt;
+
+    extern "system" {
+        fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+        fn GetExitCodeProcess(process: *mut c_void, code: *mut u32) -> i32;
+    }
+    const WAIT_OBJECT_0: u32 = 0;
+    const WAIT_TIMEOUT: u32 = 0x102;
+
+    let handle = child.as_raw_handle() as *mut c_void;
+    match unsafe { WaitForSingleObject(handle, 0) } {
+        WAIT_TIMEOUT  => Ok(None),
+        WAIT_OBJECT_0 => {
+            let mut code = 0;
+            if unsafe { GetExitCodeProcess(handle, &mut code) } == 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(Some(ExitStatus::from_raw(code)))
+            }
+        }
+        _             => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Waits for the child to exit, where there is no way to check without
+/// waiting.
+#[cfg(not(any(unix, windows)))]
+pub fn try_wait(child: &mut Child) -> io::Result<Option<ExitStatus>> {
+    child.wait().map(Some)
+}
+
+/// This process's ID, like `process::id` (Rust 1.26).
+#[cfg(unix)]
+pub fn process_id() -> u32 {
+    extern "C" {
+        fn getpid() -> i32;
+    }
+    unsafe { getpid() as u32 }
+}
+
+/// This process's ID, like `process::id` (Rust 1.26).
+#[cfg(windows)]
+pub fn process_id() -> u32 {
+    extern "system" {
+        fn GetCurrentProcessId() -> u32;
+    }
+    unsafe { GetCurrentProcessId() }
+}
+
+/// A number that distinguishes this process from others running at the same
+/// time, where there is no process ID.
+#[cfg(not(any(unix, windows)))]
+pub fn process_id() -> u32 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let marker = 0u8;
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    nanos ^ (&marker as *const u8 as usize as u32)
+}
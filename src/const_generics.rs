@@ -0,0 +1,92 @@
+use super::Probe;
+
+/// How much of const generics the compiler supports, from
+/// [`Probe::probe_const_generics`](struct.Probe.html#method.probe_const_generics).
+///
+/// Tiers are ordered by how much they allow, so they can be compared. Each
+/// is probed on its own, since later tiers need not be stabilized after
+/// earlier ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ConstGenericsTier {
+    /// No const generics.
+    None,
+    /// Const parameters of integer, `bool` and `char` types, with arguments
+    /// that are literals, parameters or constant blocks, as stabilized in
+    /// Rust 1.51.
+    MinConstGenerics,
+    /// Also arguments that are expressions over const parameters, such as
+    /// `[u8; N + 1]`.
+    ConstExpressions,
+    /// Also const parameters of structs and other user-defined types.
+    AdtConstParams,
+}
+
+/// The feature-gate names and programs that decide each tier after `None`,
+/// in order.
+const TIERS: &'static [(ConstGenericsTier, &'static str, &'static str)] = &[
+    (ConstGenericsTier::MinConstGenerics, "min_const_generics",
+     "struct Bytes<const N: usize>([u8; N]); \
+      fn main() { let _ = Bytes::<3>([0; 3]); }"),
+    (ConstGenericsTier::ConstExpressions, "generic_const_exprs",
+     "fn grow<const N: usize>() -> [u8; N + 1] where [u8; N + 1]: { [0; N + 1] } \
+      fn main() { let _ = grow::<1>(); }"),
+    (ConstGenericsTier::AdtConstParams, "adt_const_params",
+     "#[derive(PartialEq, Eq, ::std::marker::ConstParamTy)] struct Point { x: u8 } \
+      struct At<const P: Point>; \
+      fn main() { let _ = At::<{ Point { x: 1 } }>; }"),
+];
+
+impl Probe {
+    /// Classifies the compiler's support for const generics into a
+    /// [`ConstGenericsTier`](enum.ConstGenericsTier.html), the highest tier
+    /// whose probe compiles.
+    ///
+    /// Each tier is probed without a `#![feature]` attribute, and if that
+    /// fails and the compiler accepts unstable features, as nightly ones do,
+    /// again with the tier's feature gate. A crate relying on an unstable
+    /// tier must then enable the feature gate itself. Each probe is
+    /// [`named`](#method.named) after the tier's feature gate, such as
+    /// `min_const_generics`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{ConstGenericsTier, Probe};
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_const_generics() >= ConstGenericsTier::MinConstGenerics );
+    /// ```
+    ///
+    /// On nightly, unstable tiers count with their feature gates:
+    ///
+    /// ```
+    /// use std::process::Command;
+    /// use feature_probe::{ConstGenericsTier, Probe};
+    ///
+    /// let probe = Probe::new();
+    /// let version = Command::new("rustc").arg("--version").output().unwrap();
+    /// if String::from_utf8_lossy(&version.stdout).contains("-nightly") {
+    ///     assert_eq!( probe.probe_const_generics(), ConstGenericsTier::AdtConstParams );
+    /// }
+    /// ```
+    pub fn probe_const_generics(&self) -> ConstGenericsTier {
+        let unstable = self.accepts_unstable().unwrap_or(false);
+        let mut tier = ConstGenericsTier::None;
+        for &(next, name, code) in TIERS {
+            let probe = self.named(name);
+            let present = probe.probe(code)
+                || unstable && probe.probe(&format!("#![feature({})] \
+                                                     #![allow(incomplete_features, unknown_lints)] {}",
+                                                    name, code));
+            if present {
+                tier = next;
+            }
+        }
+        tier
+    }
+}
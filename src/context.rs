@@ -0,0 +1,72 @@
+use std::env;
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// The kind of build a build script is running in, as far as it can tell.
+///
+/// Some builds only need a build script to run, not to give exact answers.
+/// IDEs such as rust-analyzer run build scripts often and in the background,
+/// where heavy probing makes the editor sluggish. See
+/// [`Probe::answer_in`](struct.Probe.html#method.answer_in) for answering
+/// probes without compiling in such builds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BuildContext {
+    /// A build whose output will be used, or one that can't be told apart
+    /// from such a build. Plain `cargo check` looks like this.
+    Build,
+    /// A build run by an IDE to analyze the code.
+    Ide,
+    /// A build for interpretation by Miri, as with `cargo miri`.
+    ///
+    /// Probes in such builds compile with the ordinary compiler, whose
+    /// answers may not match what Miri supports, and spawning it may not be
+    /// allowed, so consider answering them instead.
+    Miri,
+}
+
+impl BuildContext {
+    /// Guesses the context of the current build from the environment.
+    ///
+    /// A build is taken to be for Miri if Cargo reports that the target has
+    /// `cfg(miri)`, through `CARGO_CFG_MIRI`. Otherwise, it is taken to be
+    /// an IDE build if rust-analyzer's compiler wrapper is in use, as shown
+    /// by `RA_RUSTC_WRAPPER` or by `RUSTC_WRAPPER` or
+    /// `RUSTC_WORKSPACE_WRAPPER` naming rust-analyzer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::BuildContext;
+    ///
+    /// if std::env::var_os("CARGO_CFG_MIRI").is_none()
+    ///     && std::env::var_os("RA_RUSTC_WRAPPER").is_none()
+    ///     && std::env::var_os("RUSTC_WRAPPER").is_none()
+    ///     && std::env::var_os("RUSTC_WORKSPACE_WRAPPER").is_none() {
+    ///     assert_eq!( BuildContext::detect(), BuildContext::Build );
+    /// }
+    /// ```
+    pub fn detect() -> Self {
+        if env::var_os("CARGO_CFG_MIRI").is_some() {
+            BuildContext::Miri
+        } else if env::var_os("RA_RUSTC_WRAPPER").is_some()
+            || names_rust_analyzer("RUSTC_WRAPPER")
+            || names_rust_analyzer("RUSTC_WORKSPACE_WRAPPER")
+        {
+            BuildContext::Ide
+        } else {
+            BuildContext::Build
+        }
+    }
+}
+
+/// Whether the environment variable names a rust-analyzer executable.
+fn names_rust_analyzer(var: &str) -> bool {
+    match env::var_os(var) {
+        Some(path) => Path::new(&path).file_stem()
+            .and_then(OsStr::to_str)
+            .map(|stem| stem.contains("rust-analyzer"))
+            .unwrap_or(false),
+        None       => false,
+    }
+}
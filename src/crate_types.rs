@@ -0,0 +1,73 @@
+use std::io;
+
+use super::Probe;
+
+/// The crate types [`Probe::supported_crate_types`] tries, in the order it
+/// lists them.
+///
+/// [`Probe::supported_crate_types`]: struct.Probe.html#method.supported_crate_types
+pub const CRATE_TYPES: &'static [&'static str] = &[
+    "bin", "lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro",
+];
+
+impl Probe {
+    /// Determines which of the [`CRATE_TYPES`] the compiler can produce for
+    /// the probe's target, so that a crate that optionally builds a `cdylib`,
+    /// for example, can configure its build and emit accurate `cfg`s.
+    ///
+    /// Each crate type is tried on an empty crate. Types the target can't
+    /// produce, such as `dylib` on `wasm32-unknown-unknown` or with
+    /// `-C target-feature=+crt-static` on most Linux targets, are dropped by
+    /// `rustc` with a warning, which this recognizes.
+    ///
+    /// [`CRATE_TYPES`]: constant.CRATE_TYPES.html
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with, or with
+    /// kind `InvalidData` if the empty crate fails to compile for some other
+    /// reason, such as the target's standard library not being installed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// let types = probe.supported_crate_types().unwrap();
+    /// assert!( types.contains(&"lib") );
+    ///
+    /// if cfg!(all(target_os = "linux", target_env = "gnu")) {
+    ///     assert!( types.contains(&"cdylib") );
+    ///
+    ///     probe.arg("-C").arg("target-feature=+crt-static");
+    ///     assert!( ! probe.supported_crate_types().unwrap().contains(&"cdylib") );
+    /// }
+    /// ```
+    pub fn supported_crate_types(&self) -> io::Result<Vec<&'static str>> {
+        let mut supported = Vec::new();
+
+        for &crate_type in CRATE_TYPES {
+            let mut probe = self.clone();
+            probe.crate_type = Some(crate_type);
+
+            let mut command = String::new();
+            let mut diagnostics = String::new();
+            let compiled = probe.compile_capturing("#![allow(dead_code)] fn main() { }",
+                                                   &mut command, &mut diagnostics)?;
+
+            if diagnostics.contains("dropping unsupported crate type") {
+                continue;
+            }
+            if !compiled {
+                let error = diagnostics.lines().find(|line| line.starts_with("error")).unwrap_or("");
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          format!("crate type {} probe failed: {}",
+                                                  crate_type, error)));
+            }
+            supported.push(crate_type);
+        }
+
+        Ok(supported)
+    }
+}
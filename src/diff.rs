@@ -0,0 +1,135 @@
+use std::fmt;
+use std::io;
+
+use super::{Probe, ProbeReport};
+
+/// How the answers of the same named probes differ between two
+/// configurations, from [`Probe::diff`](struct.Probe.html#method.diff).
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::{DiffReport, ProbeReport};
+///
+/// let mut stable = ProbeReport::new();
+/// stable.record("i128", true);
+/// stable.record("never_type", false);
+/// stable.record("box_syntax", true);
+///
+/// let mut beta = ProbeReport::new();
+/// beta.record("i128", true);
+/// beta.record("never_type", true);
+/// beta.record("box_syntax", false);
+///
+/// let diff = DiffReport::between(&stable, &beta);
+/// assert!( diff.has_changes() );
+/// assert_eq!( diff.gained(), vec!["never_type"] );
+/// assert_eq!( diff.lost(), vec!["box_syntax"] );
+/// assert_eq!( diff.to_string(),
+///             "never_type: absent -> present\n\
+///              box_syntax: present -> absent\n" );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiffReport {
+    before: ProbeReport,
+    after:  ProbeReport,
+}
+
+impl DiffReport {
+    /// Compares two reports. Probes that only one of them has are ignored.
+    pub fn between(before: &ProbeReport, after: &ProbeReport) -> Self {
+        DiffReport {
+            before: before.clone(),
+            after:  after.clone(),
+        }
+    }
+
+    /// The answers of the first configuration.
+    pub fn before(&self) -> &ProbeReport {
+        &self.before
+    }
+
+    /// The answers of the second configuration.
+    pub fn after(&self) -> &ProbeReport {
+        &self.after
+    }
+
+    /// The names of the probes whose answers changed, with the answers
+    /// before and after, in the order of the first report.
+    pub fn changes(&self) -> Vec<(&str, bool, bool)> {
+        self.before.iter()
+            .filter_map(|(name, before)| match self.after.get(name) {
+                Some(after) if after != before => Some((name, before, after)),
+                _                              => None,
+            })
+            .collect()
+    }
+
+    /// Whether any answer changed.
+    pub fn has_changes(&self) -> bool {
+        !self.changes().is_empty()
+    }
+
+    /// The probes that failed before and succeed after.
+    pub fn gained(&self) -> Vec<&str> {
+        self.changes().into_iter().filter(|change| change.2).map(|change| change.0).collect()
+    }
+
+    /// The probes that succeeded before and fail after, which usually calls
+    /// for attention.
+    pub fn lost(&self) -> Vec<&str> {
+        self.changes().into_iter().filter(|change| change.1).map(|change| change.0).collect()
+    }
+}
+
+/// Lists each change as `NAME: BEFORE -> AFTER`, one per line.
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let answer = |present| if present { "present" } else { "absent" };
+        for (name, before, after) in self.changes() {
+            writeln!(f, "{}: {} -> {}", name, answer(before), answer(after))?;
+        }
+        Ok(())
+    }
+}
+
+impl Probe {
+    /// Runs the same named probe programs with this probe and another, such
+    /// as one for the stable toolchain and one for beta, or one for the host
+    /// and one for the target, and reports which answers differ.
+    ///
+    /// Each probe is given as a name and a whole program, and run with
+    /// [`named`](#method.named) copies of both probes.
+    ///
+    /// # Errors
+    ///
+    /// If either compiler cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let old = Probe::builder().edition("2015").build();
+    /// let new = Probe::builder().edition("2018").build();
+    /// let diff = old.diff(&new, &[
+    ///     ("dyn_keyword", "fn main() { let dyn = 1; let _ = dyn; }"),
+    ///     ("u32",         "fn main() { let _: u32 = 0; }"),
+    /// ]).unwrap();
+    ///
+    /// assert_eq!( diff.lost(), vec!["dyn_keyword"] );
+    /// assert!( diff.gained().is_empty() );
+    /// ```
+    pub fn diff(&self, other: &Probe, probes: &[(&str, &str)]) -> io::Result<DiffReport> {
+        let mut before = ProbeReport::new();
+        let mut after = ProbeReport::new();
+
+        for &(name, code) in probes {
+            before.record(name, self.named(name).probe_result(code)?);
+            after.record(name, other.named(name).probe_result(code)?);
+        }
+
+        Ok(DiffReport::between(&before, &after))
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use super::ProbeOutcome;
+
+/// What happened in one probe, as passed to a probe's
+/// [`observer`](struct.Probe.html#method.observer).
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use feature_probe::Probe;
+///
+/// let seen = Arc::new(Mutex::new(Vec::new()));
+/// let log = seen.clone();
+///
+/// let mut probe = Probe::new();
+/// probe.observer(move |event| {
+///     log.lock().unwrap().push((event.name().map(str::to_owned), event.outcome()));
+/// });
+/// probe.named("i128").probe_type("i128");
+/// probe.probe_type("u512");
+///
+/// let seen = seen.lock().unwrap();
+/// assert_eq!( seen.len(), 2 );
+/// assert_eq!( seen[0].0, Some(String::from("i128")) );
+/// assert!(    seen[0].1.is_present() );
+/// assert_eq!( seen[1].0, None );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ProbeEvent<'a> {
+    name:      Option<&'a str>,
+    code:      &'a str,
+    code_hash: u64,
+    elapsed:   Duration,
+    cached:    bool,
+    outcome:   ProbeOutcome,
+}
+
+/// Describes a probe for its observer.
+pub fn new<'a>(name: Option<&'a str>, code: &'a str, code_hash: u64, elapsed: Duration,
+               cached: bool, outcome: ProbeOutcome) -> ProbeEvent<'a> {
+    ProbeEvent {
+        name:      name,
+        code:      code,
+        code_hash: code_hash,
+        elapsed:   elapsed,
+        cached:    cached,
+        outcome:   outcome,
+    }
+}
+
+impl<'a> ProbeEvent<'a> {
+    /// The name of the probe, if it has one.
+    ///
+    /// Probes are named with [`Probe::named`](struct.Probe.html#method.named),
+    /// and by methods that build a [`ProbeReport`](struct.ProbeReport.html),
+    /// which use the names they record.
+    pub fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+
+    /// The probe program.
+    pub fn code(&self) -> &'a str {
+        self.code
+    }
+
+    /// A hash of the probe program and the configuration it was compiled
+    /// with, which identifies the same probe across builds.
+    pub fn code_hash(&self) -> u64 {
+        self.code_hash
+    }
+
+    /// How long the probe took.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Whether the answer came from a cache or a preset answer rather than
+    /// from the compiler.
+    pub fn cached(&self) -> bool {
+        self.cached
+    }
+
+    /// The answer, which is `Unknown` if the probe could not be carried out.
+    pub fn outcome(&self) -> ProbeOutcome {
+        self.outcome
+    }
+}
@@ -0,0 +1,49 @@
+
+use super::Probe;
+
+impl Probe {
+    /// Carries out a probe and explains its answer, for finding out why a
+    /// feature is missing on some machine.
+    ///
+    /// The explanation gives the probe's name (see
+    /// [`named`](#method.named)), the answer, the exact command that was run,
+    /// the probe program, and the compiler's diagnostics. The probe is
+    /// compiled afresh in a temporary directory, ignoring cached and preset
+    /// answers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let explanation = probe.named("u512").explain("fn main() { let _: u512 = 0; }");
+    /// assert!( explanation.starts_with("probe u512: absent\n") );
+    /// assert!( explanation.contains("fn main() { let _: u512 = 0; }") );
+    /// assert!( explanation.contains("diagnostics:\n    error") );
+    /// ```
+    pub fn explain(&self, code: &str) -> String {
+        let mut command = String::new();
+        let mut diagnostics = String::new();
+
+        let answer = match self.compile_capturing(code, &mut command, &mut diagnostics) {
+            Ok(true)  => String::from("present"),
+            Ok(false) => String::from("absent"),
+            Err(e)    => format!("unknown ({})", e),
+        };
+
+        let mut out = format!("probe {}: {}\n",
+                              self.label.as_ref().map_or("(unnamed)", String::as_str),
+                              answer);
+        out.push_str(&format!("command: {}\n", if command.is_empty() { "(none)" } else { &command }));
+        out.push_str("source:\n");
+        out.push_str(&indent(code));
+        out.push_str("diagnostics:\n");
+        out.push_str(&indent(if diagnostics.is_empty() { "(none)" } else { &diagnostics }));
+        out
+    }
+}
+
+fn indent(text: &str) -> String {
+    text.lines().map(|line| format!("    {}\n", line)).collect()
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeSet;
+use std::io;
+
+use super::Probe;
+
+/// A target feature, as listed by `rustc --print target-features`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TargetFeature {
+    /// The name, as used in `#[target_feature(enable = "…")]` and
+    /// `cfg(target_feature = "…")`.
+    pub name:        String,
+    /// Whether the feature is enabled when compiling for the target with the
+    /// probe's arguments, such as `-C target-cpu`.
+    pub enabled:     bool,
+    /// The compiler's one-line description.
+    pub description: String,
+}
+
+impl Probe {
+    /// Lists the target features the compiler supports for the probe's
+    /// target, and which of them are enabled.
+    ///
+    /// Only features usable from Rust code are listed, not the additional
+    /// code-generation features LLVM supports. Arguments given with
+    /// [`arg`](#method.arg), such as `-C target-cpu=native`, affect which
+    /// features are enabled.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc` cannot be run or its output cannot be understood.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let features = probe.target_features().unwrap();
+    ///
+    /// if cfg!(target_arch = "x86_64") {
+    ///     let sse2 = features.iter().find(|feature| feature.name == "sse2").unwrap();
+    ///     assert!( sse2.enabled );
+    /// }
+    /// ```
+    pub fn target_features(&self) -> io::Result<Vec<TargetFeature>> {
+        let listing = self.print_info("target-features")?;
+        let enabled = self.enabled_target_features()?;
+
+        let mut lines = listing.lines();
+        if !lines.next().map_or(false, |line| line.starts_with("Features supported by rustc")) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "unrecognized `--print target-features` output"));
+        }
+
+        let mut features = Vec::new();
+        for line in lines {
+            // The list ends at a blank line, before LLVM's own features.
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+
+            let mut parts = line.splitn(2, " - ");
+            let name = parts.next().unwrap_or("").trim();
+            features.push(TargetFeature {
+                name:        name.to_owned(),
+                enabled:     enabled.contains(name),
+                description: parts.next().unwrap_or("").trim().to_owned(),
+            });
+        }
+
+        Ok(features)
+    }
+
+    /// Lists the values of a key-value configuration option, such as
+    /// `target_os` or `target_has_atomic`, when compiling for the probe's
+    /// target, from `rustc --print cfg`.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc` cannot be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if cfg!(target_os = "linux") {
+    ///     assert_eq!( probe.target_cfg_values("target_os").unwrap(), vec!["linux"] );
+    /// }
+    /// assert!( probe.target_cfg_values("no_such_cfg").unwrap().is_empty() );
+    /// ```
+    pub fn target_cfg_values(&self, name: &str) -> io::Result<Vec<String>> {
+        let prefix = format!("{}=\"", name);
+        Ok(self.print_info("cfg")?
+            .lines()
+            .filter_map(|line| {
+                if line.starts_with(&prefix) && line.ends_with('"') && line.len() > prefix.len() {
+                    Some(line[prefix.len() .. line.len() - 1].to_owned())
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// The target features enabled for the probe's target, from
+    /// `rustc --print cfg`.
+    fn enabled_target_features(&self) -> io::Result<BTreeSet<String>> {
+        Ok(self.target_cfg_values("target_feature")?.into_iter().collect())
+    }
+}
@@ -0,0 +1,58 @@
+use std::ffi::OsStr;
+
+use compat;
+
+/// A 64-bit FNV-1a hash over length-prefixed fields.
+///
+/// Unlike `std::hash`, the result is the same across Rust versions and
+/// platforms, so it can key caches that outlive a single build.
+#[derive(Clone, Debug)]
+pub struct Fingerprinter {
+    state: u64,
+}
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64        = 0x0000_0100_0000_01b3;
+
+impl Fingerprinter {
+    pub fn new() -> Self {
+        Fingerprinter {
+            state: OFFSET_BASIS,
+        }
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= u64::from(byte);
+            self.state = self.state.wrapping_mul(PRIME);
+        }
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.write_raw(&compat::le_bytes(bytes.len() as u64));
+        self.write_raw(bytes);
+        self
+    }
+
+    pub fn str(&mut self, s: &str) -> &mut Self {
+        self.bytes(s.as_bytes())
+    }
+
+    pub fn os_str(&mut self, s: &OsStr) -> &mut Self {
+        self.str(&s.to_string_lossy())
+    }
+
+    pub fn option<T, F>(&mut self, value: Option<T>, f: F) -> &mut Self
+        where F: FnOnce(&mut Self, T)
+    {
+        match value {
+            None        => { self.write_raw(&[0]); }
+            Some(value) => { self.write_raw(&[1]); f(self, value); }
+        }
+        self
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
@@ -0,0 +1,154 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use super::{cancel, jobs, CancellationToken, Probe};
+
+/// A probe running in the background, which resolves to its result.
+///
+/// Create one with [`Probe::probe_async`](struct.Probe.html#method.probe_async).
+/// Dropping the future cancels the probe, killing its compiler if it is
+/// running. This needs the `async` feature.
+#[derive(Debug)]
+pub struct ProbeFuture {
+    state:  Arc<Mutex<State>>,
+    cancel: CancellationToken,
+}
+
+#[derive(Debug)]
+#[clippy::msrv = "1.36.0"]
+struct State {
+    result: Option<io::Result<bool>>,
+    waker:  Option<Waker>,
+}
+
+#[clippy::msrv = "1.36.0"]
+impl Future for ProbeFuture {
+    type Output = io::Result<bool>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None         => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for ProbeFuture {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+#[clippy::msrv = "1.36.0"]
+impl Probe {
+    /// Starts probing for whether a whole program can be compiled, without
+    /// blocking, and returns a future of the result.
+    ///
+    /// The compiler starts right away, whether or not the future is polled,
+    /// so several probes started together compile concurrently. The future
+    /// doesn't depend on any particular async runtime, so rather than an
+    /// async process API, each probe has a thread of its own that waits for
+    /// the compiler. Dropping the future cancels the probe, as a
+    /// [`CancellationToken`](struct.CancellationToken.html) would, and the
+    /// thread exits once the compiler has been killed. This needs the
+    /// `async` feature.
+    ///
+    /// So as not to oversubscribe the machine, at most `NUM_JOBS` probes,
+    /// which Cargo sets for build scripts, or otherwise `CARGO_BUILD_JOBS`,
+    /// compile at once across the process. With the `jobserver` feature,
+    /// probes instead take jobs from Cargo's jobserver, sharing them with the
+    /// rest of the build.
+    ///
+    /// # Errors
+    ///
+    /// The future resolves to an error if the child `rustc` cannot be
+    /// started or communicated with, as
+    /// [`probe_result`](#method.probe_result) would return.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::future::Future;
+    /// use std::sync::Arc;
+    /// use std::task::{Context, Poll, Wake};
+    /// use std::thread::{self, Thread};
+    /// use feature_probe::Probe;
+    ///
+    /// // A minimal executor; real code would use its async runtime's.
+    /// struct Unparker(Thread);
+    /// impl Wake for Unparker {
+    ///     fn wake(self: Arc<Self>) { self.0.unpark(); }
+    /// }
+    /// fn block_on<F: Future>(future: F) -> F::Output {
+    ///     let mut future = Box::pin(future);
+    ///     let waker = Arc::new(Unparker(thread::current())).into();
+    ///     let mut cx = Context::from_waker(&waker);
+    ///     loop {
+    ///         match future.as_mut().poll(&mut cx) {
+    ///             Poll::Ready(output) => return output,
+    ///             Poll::Pending       => thread::park(),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let probe = Probe::new();
+    /// let i128 = probe.probe_async("fn main() { let _: i128 = 0; }");
+    /// let u512 = probe.probe_async("fn main() { let _: u512 = 0; }");
+    /// assert!(   block_on(i128).unwrap() );
+    /// assert!( ! block_on(u512).unwrap() );
+    /// ```
+    ///
+    /// Dropping the future kills the compiler, here a stand-in that would
+    /// take a minute:
+    ///
+    /// ```
+    /// # #[cfg(unix)] fn main() {
+    /// use std::sync::{mpsc, Mutex};
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use feature_probe::Probe;
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let sender = Mutex::new(sender);
+    /// let mut probe = Probe::new();
+    /// probe.wrapper_command(&["sh", "-c", "case $1 in -vV) exec \"$0\" \"$@\";; esac; exec sleep 60"]);
+    /// probe.observer(move |event| { let _ = sender.lock().unwrap().send(event.elapsed()); });
+    ///
+    /// let future = probe.probe_async("fn main() { }");
+    /// thread::sleep(Duration::from_millis(500));
+    /// drop(future);
+    /// let elapsed = receiver.recv_timeout(Duration::from_secs(30)).unwrap();
+    /// assert!( elapsed < Duration::from_secs(30) );
+    /// # }
+    /// # #[cfg(not(unix))] fn main() { }
+    /// ```
+    pub fn probe_async(&self, code: &str) -> ProbeFuture {
+        let state = Arc::new(Mutex::new(State { result: None, waker: None }));
+
+        let token     = cancel::child(self.cancel.as_ref());
+        let mut probe = self.clone();
+        probe.cancel  = Some(token.clone());
+        let code      = code.to_owned();
+        let shared    = state.clone();
+        thread::spawn(move || {
+            let _slot  = jobs::acquire();
+            let result = probe.probe_result(&code);
+
+            let mut state = shared.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+
+        ProbeFuture { state, cancel: token }
+    }
+}
@@ -0,0 +1,85 @@
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use super::{Probe, ProbeBackend, RecordingReporter};
+
+/// Runs build-script logic against a fake backend and asserts that it emits
+/// exactly the directives listed in a golden file.
+///
+/// The `build` closure receives a [`Probe`](struct.Probe.html) that uses the
+/// given `backend` and records its directives instead of printing them. The
+/// golden file holds the expected directives, one `cargo:KEY=VALUE` line
+/// each, in order. When the environment variable `FEATURE_PROBE_BLESS` is
+/// set, the golden file is written from the recorded directives instead of
+/// being checked, which is how golden files are created and updated.
+///
+/// # Panics
+///
+/// If the recorded directives differ from the golden file, or the golden
+/// file cannot be read or written.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::File;
+/// use std::io::Write;
+/// use feature_probe::{assert_golden, ScriptedBackend};
+///
+/// let golden = std::env::temp_dir().join("feature_probe_doc_golden.txt");
+/// File::create(&golden).unwrap()
+///     .write_all(b"cargo:rustc-cfg=int_128\n").unwrap();
+///
+/// let backend = ScriptedBackend::new()
+///     .answer("i128", true)
+///     .answer("u512", false);
+///
+/// assert_golden(backend, &golden, |probe| {
+///     if probe.probe_type("i128") {
+///         probe.emit_cfg("int_128");
+///     }
+///     if probe.probe_type("u512") {
+///         probe.emit_cfg("int_512");
+///     }
+/// });
+/// ```
+pub fn assert_golden<B, P, F>(backend: B, golden: P, build: F)
+    where B: ProbeBackend + 'static,
+          P: AsRef<Path>,
+          F: FnOnce(&mut Probe)
+{
+    let golden = golden.as_ref();
+    let recorder = RecordingReporter::new();
+
+    let mut probe = Probe::new();
+    probe.backend(backend).reporter(recorder.clone());
+    build(&mut probe);
+
+    let mut actual = String::new();
+    for directive in recorder.directives() {
+        actual.push_str(&directive);
+        actual.push('\n');
+    }
+
+    if env::var_os("FEATURE_PROBE_BLESS").is_some() {
+        File::create(golden)
+            .and_then(|mut file| file.write_all(actual.as_bytes()))
+            .unwrap_or_else(|e| panic!("assert_golden: writing {}: {}", golden.display(), e));
+        return;
+    }
+
+    let mut expected = String::new();
+    File::open(golden)
+        .and_then(|mut file| file.read_to_string(&mut expected))
+        .unwrap_or_else(|e| panic!("assert_golden: reading {}: {} \
+                                    (set FEATURE_PROBE_BLESS=1 to create it)",
+                                   golden.display(), e));
+
+    if actual.lines().ne(expected.lines()) {
+        panic!("assert_golden: directives differ from {} \
+                (set FEATURE_PROBE_BLESS=1 to update it)\n\
+                expected:\n{}\nactual:\n{}",
+               golden.display(), expected, actual);
+    }
+}
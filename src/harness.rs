@@ -0,0 +1,48 @@
+/// The way [`Probe::probe_type_with`](struct.Probe.html#method.probe_type_with)
+/// mentions the type it probes for.
+///
+/// Different harnesses answer slightly different questions, since a type may
+/// be nameable without being usable everywhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TypeHarness {
+    /// `pub type T = TYPE;`, which checks only that the type can be named.
+    /// This is what [`Probe::probe_type`](struct.Probe.html#method.probe_type)
+    /// uses.
+    Alias,
+    /// A field of type `PhantomData<TYPE>`, which also checks that the type
+    /// is well formed, and works for unsized types.
+    PhantomData,
+    /// A function parameter of type `&TYPE`, which works for unsized types.
+    Reference,
+    /// A function parameter of type `Box<TYPE>`.
+    Boxed,
+    /// A function parameter of type `TYPE`, which requires the type to be
+    /// sized.
+    Value,
+}
+
+impl TypeHarness {
+    /// Renders the probe program for the given type.
+    pub fn render(self, type_name: &str) -> String {
+        match self {
+            TypeHarness::Alias =>
+                format!("pub type T = {}; fn main() {{ }}", type_name),
+            TypeHarness::PhantomData =>
+                format!("pub struct S(pub ::std::marker::PhantomData<{}>); fn main() {{ }}",
+                        type_name),
+            TypeHarness::Reference =>
+                format!("pub fn f(_: &{}) {{ }} fn main() {{ }}", type_name),
+            TypeHarness::Boxed =>
+                format!("pub fn f(_: Box<{}>) {{ }} fn main() {{ }}", type_name),
+            TypeHarness::Value =>
+                format!("pub fn f(_: {}) {{ }} fn main() {{ }}", type_name),
+        }
+    }
+}
+
+impl Default for TypeHarness {
+    fn default() -> Self {
+        TypeHarness::Alias
+    }
+}
@@ -0,0 +1,193 @@
+use std::cmp;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::{compat, temp, Probe, EMIT_KINDS};
+
+impl Probe {
+    /// Compiles the given program and returns the output of the given
+    /// `--emit` kind, such as `"asm"` or `"llvm-ir"`, as text, or `None` if
+    /// the program doesn't compile.
+    ///
+    /// This is for probes that need evidence of how code is compiled rather
+    /// than whether it compiles. The output depends on the optimization
+    /// level, so pass flags such as `-O` with [`arg`](#method.arg) to inspect
+    /// what a release build would produce.
+    ///
+    /// # Errors
+    ///
+    /// If `kind` isn't a text output kind, or `rustc` cannot be started or
+    /// communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let ir = probe.emitted("fn main() { }", "llvm-ir").unwrap().unwrap();
+    /// assert!( ir.contains("define") );
+    /// assert!( probe.emitted("fn main() { oops }", "asm").unwrap().is_none() );
+    /// assert!( probe.emitted("fn main() { }", "obj").is_err() );
+    /// ```
+    pub fn emitted(&self, code: &str, kind: &str) -> io::Result<Option<String>> {
+        if !TEXT_EMIT_KINDS.contains(&kind) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("cannot inspect emit kind {:?}", kind)));
+        }
+
+        match self.emit_to_temp(code, kind)? {
+            Some((_dir, path)) =>
+                compat::read(&path).map(|bytes| Some(String::from_utf8_lossy(&bytes).into_owned())),
+            None => Ok(None),
+        }
+    }
+
+    /// Compiles the given program and returns the size in bytes of the output
+    /// of the given `--emit` kind, or `None` if the program doesn't compile.
+    ///
+    /// With `"link"`, this is the size of the linked binary, which needs a
+    /// working linker for the target. Flags such as `-O` or
+    /// `-C opt-level=s` passed with [`arg`](#method.arg) affect the size.
+    ///
+    /// # Errors
+    ///
+    /// If `kind` isn't one `rustc` knows, or `rustc` cannot be started or
+    /// communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let small = probe.probe_artifact_size("fn main() { }", "obj").unwrap().unwrap();
+    /// let large = probe.probe_artifact_size(
+    ///     "fn main() { println!(\"{:?}\", std::env::args().collect::<Vec<_>>()) }",
+    ///     "obj").unwrap().unwrap();
+    /// assert!( 0 < small && small < large );
+    /// ```
+    pub fn probe_artifact_size(&self, code: &str, kind: &str) -> io::Result<Option<u64>> {
+        if !EMIT_KINDS.contains(&kind) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("unknown emit kind {:?}", kind)));
+        }
+
+        match self.emit_to_temp(code, kind)? {
+            Some((_dir, path)) => fs::metadata(&path).map(|metadata| Some(metadata.len())),
+            None               => Ok(None),
+        }
+    }
+
+    /// Measures how long the given program takes to compile, returning the
+    /// median wall time of `iterations` compilations, or `None` if the
+    /// program doesn't compile.
+    ///
+    /// An untimed compilation runs first, so that caches are warm and the
+    /// compiler's own files are loaded before timing starts. At least one
+    /// compilation is timed. Timings include starting `rustc`, so compare
+    /// them against a trivial program's rather than reading them in
+    /// isolation. Every compilation is real: preset, cached and
+    /// [shared](#method.share_answers) answers are ignored.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_compile_time("fn main() { }", 3).unwrap().is_some() );
+    /// assert!( probe.probe_compile_time("fn main() { oops }", 3).unwrap().is_none() );
+    ///
+    /// let mut shared = Probe::new();
+    /// shared.share_answers(true);
+    /// assert!( shared.probe_type("u8") );
+    /// assert!( shared.probe_compile_time("fn main() { }", 3).unwrap().is_some() );
+    /// assert_eq!( shared.stats().compilations, 1 + 4 );
+    /// ```
+    pub fn probe_compile_time(&self, code: &str, iterations: usize)
+                              -> io::Result<Option<Duration>> {
+        let probe = self.compiling_afresh();
+        if !probe.probe_result_in_temp(code)? {
+            return Ok(None);
+        }
+
+        let iterations = cmp::max(iterations, 1);
+        let mut times = Vec::with_capacity(iterations);
+        for _ in 0 .. iterations {
+            let start = Instant::now();
+            if !probe.probe_result_in_temp(code)? {
+                return Ok(None);
+            }
+            times.push(start.elapsed());
+        }
+
+        times.sort();
+        Ok(Some(times[times.len() / 2]))
+    }
+
+    /// Compiles with the output of the given kind going to a file in a fresh
+    /// temporary directory, returning the directory, which must be kept
+    /// alive while the file is used, and the file's path.
+    fn emit_to_temp(&self, code: &str, kind: &str)
+                    -> io::Result<Option<(temp::TempDir, PathBuf)>> {
+        if !self.profile().accepts_rustc_flags() || !self.profile().supports_emit(kind) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("{:?} cannot emit {:?} to a chosen file",
+                                              self.profile(), kind)));
+        }
+
+        let dir  = temp::TempDir::new()?;
+        let path = dir.path().join("probe.out");
+
+        let mut probe = self.compiling_afresh();
+        probe.emit = format!("{}={}", kind, path.display());
+        if kind == "link" {
+            probe.crate_type = None;
+        }
+
+        if probe.compile(probe.command_in(Some(dir.path())), code)? {
+            Ok(Some((dir, path)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Probes whether the given program compiles to output of the given
+    /// `--emit` kind that contains `pattern`.
+    ///
+    /// For example, this can check that an operation lowers to a particular
+    /// instruction on the target, or that it doesn't call into a runtime
+    /// library. See [`emitted`](#method.emitted).
+    ///
+    /// # Panics
+    ///
+    /// If `kind` isn't a text output kind, or the child `rustc` cannot be
+    /// started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let code = "#[no_mangle] pub extern \"C\" fn probe_answer() -> u32 { 42 } \
+    ///             fn main() { }";
+    /// assert!(   probe.probe_emit_contains(code, "asm", "probe_answer") );
+    /// assert!( ! probe.probe_emit_contains(code, "asm", "probe_question") );
+    /// ```
+    pub fn probe_emit_contains(&self, code: &str, kind: &str, pattern: &str) -> bool {
+        self.emitted(code, kind)
+            .expect("Probe::probe_emit_contains")
+            .map_or(false, |output| output.contains(pattern))
+    }
+}
+
+/// The `--emit` kinds that produce text.
+const TEXT_EMIT_KINDS: &'static [&'static str] = &["asm", "llvm-ir", "mir"];
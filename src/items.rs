@@ -0,0 +1,807 @@
+use std::io;
+
+use super::{compat, Probe, ProbeReport, Version, INPUT_FILE};
+
+/// The first release that accepts `dyn Trait`.
+const DYN_VERSION: Version = Version { major: 1, minor: 27, patch: 0 };
+
+/// The type `probe_expression_type_name` assigns expressions to, which no
+/// expression of the caller's can have.
+const MISMATCHED_TYPE: &'static str = "FeatureProbeNoType";
+
+impl Probe {
+    /// Probes whether the enum at the given path has the named variant.
+    ///
+    /// The variant is referenced in a pattern, so this works for unit, tuple
+    /// and struct variants alike. The path must not have generic arguments.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_variant("std::io::ErrorKind", "NotFound") );
+    /// assert!( ! probe.probe_variant("std::io::ErrorKind", "NoSuchKind") );
+    /// ```
+    pub fn probe_variant(&self, enum_path: &str, variant: &str) -> bool {
+        self.probe(&format!("#[allow(unreachable_patterns)] \
+                             fn f(x: &{0}) {{ match *x {{ {0}::{1} {{ .. }} => (), _ => () }} }} \
+                             fn main() {{ let _ = f; }}",
+                            enum_path, variant))
+    }
+
+    /// Probes whether the given type has an accessible field of the given
+    /// name that a value of `field_type` can be assigned to.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_field("std::ops::Range<u8>", "start", "u8") );
+    /// assert!( ! probe.probe_field("std::ops::Range<u8>", "start", "String") );
+    /// assert!( ! probe.probe_field("std::ops::Range<u8>", "middle", "u8") );
+    /// ```
+    pub fn probe_field(&self, type_name: &str, field: &str, field_type: &str) -> bool {
+        self.probe(&format!("fn f(x: &mut {}, v: {}) {{ x.{} = v; }} \
+                             fn main() {{ let _ = f; }}",
+                            type_name, field_type, field))
+    }
+
+    /// Probes whether the function or method at the given path can be called
+    /// with arguments of the given types, producing a value of the given
+    /// return type.
+    ///
+    /// Methods are called by path, so the receiver type comes first in
+    /// `arg_types`. The call is made in an `unsafe` block, so this works for
+    /// unsafe functions too.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_callable("u32::checked_add", &["u32", "u32"], "Option<u32>") );
+    /// assert!( ! probe.probe_callable("u32::checked_add", &["u32", "u32"], "u32") );
+    /// assert!( ! probe.probe_callable("u32::checked_add", &["u32"], "Option<u32>") );
+    /// ```
+    pub fn probe_callable(&self, path: &str, arg_types: &[&str], return_type: &str) -> bool {
+        let params: Vec<String> = arg_types.iter().enumerate()
+            .map(|(i, arg_type)| format!("a{}: {}", i, arg_type))
+            .collect();
+        let args: Vec<String> = (0 .. arg_types.len())
+            .map(|i| format!("a{}", i))
+            .collect();
+
+        self.probe(&format!("#[allow(unused_unsafe)] \
+                             fn f({}) -> {} {{ unsafe {{ {}({}) }} }} \
+                             fn main() {{ let _ = f; }}",
+                            params.join(", "), return_type, path, args.join(", ")))
+    }
+
+    /// Probes whether the given impl is permitted.
+    ///
+    /// The code is compiled as a library crate, so it may define the local
+    /// types and traits the impl mentions alongside the impl itself. This
+    /// answers coherence questions, such as whether the orphan rules allow
+    /// the impl or whether a blanket impl in `std` overlaps it. The impl
+    /// must be complete, since missing items are an error too.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_impl("pub struct Wrapper<T>(T); \
+    ///                              impl<T> From<T> for Wrapper<T> { \
+    ///                                  fn from(t: T) -> Self { Wrapper(t) } \
+    ///                              }") );
+    /// assert!( ! probe.probe_impl("pub struct Wrapper; \
+    ///                              impl<T> From<T> for Wrapper { \
+    ///                                  fn from(_: T) -> Self { Wrapper } \
+    ///                              }") );
+    /// ```
+    pub fn probe_impl(&self, code: &str) -> bool {
+        self.probe_items_as_lib(code)
+    }
+
+    /// Probes whether matching the given variants of the enum at the given
+    /// path, with no wildcard arm, is exhaustive.
+    ///
+    /// This is false if the enum has variants missing from the list, or if it
+    /// is `#[non_exhaustive]`, as external enums in `std` often are. Crates
+    /// can use it to decide between generating exhaustive matches and adding
+    /// a wildcard arm. The path must not have generic arguments.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_exhaustive_match("std::cmp::Ordering",
+    ///                                         &["Less", "Equal", "Greater"]) );
+    /// assert!( ! probe.probe_exhaustive_match("std::cmp::Ordering",
+    ///                                         &["Less", "Greater"]) );
+    /// ```
+    pub fn probe_exhaustive_match(&self, enum_path: &str, variants: &[&str]) -> bool {
+        let arms: Vec<String> = variants.iter()
+            .map(|variant| format!("{}::{} {{ .. }} => (),", enum_path, variant))
+            .collect();
+
+        self.probe(&format!("fn f(x: &{}) {{ match *x {{ {} }} }} \
+                             fn main() {{ let _ = f; }}",
+                            enum_path, arms.join(" ")))
+    }
+
+    /// Probes whether the named macro, invoked in expression position with
+    /// the given arguments, expands and type-checks.
+    ///
+    /// The macro name may be given with or without its trailing `!`. This
+    /// detects macros that gained new forms, such as `format!` capturing
+    /// variables implicitly.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_macro_call("vec!", "1, 2, 3") );
+    /// assert!( ! probe.probe_macro_call("vec!", "1, \"two\"") );
+    /// ```
+    pub fn probe_macro_call(&self, name: &str, args: &str) -> bool {
+        let name = if name.ends_with('!') { &name[.. name.len() - 1] } else { name };
+        self.probe(&format!("fn main() {{ let _ = {}!({}); }}", name, args))
+    }
+
+    /// Probes whether `main` may return the given type, with the given body.
+    ///
+    /// Binary crates and code generators can use this to decide between
+    /// `fn main() -> Result<(), E>` and exiting explicitly on old toolchains.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_main_returning("Result<(), std::io::Error>", "Ok(())") );
+    /// assert!( ! probe.probe_main_returning("u32", "0") );
+    /// ```
+    pub fn probe_main_returning(&self, return_type: &str, body: &str) -> bool {
+        self.probe(&format!("fn main() -> {} {{ {} }}", return_type, body))
+    }
+
+    /// Probes whether the given type implements
+    /// `std::process::Termination`, and so may be returned from `main`.
+    ///
+    /// The trait is only nameable from Rust 1.61, so this is false on older
+    /// toolchains even for types that `main` may return there; use
+    /// [`probe_main_returning`](#method.probe_main_returning) for those.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( ! probe.probe_termination("u32") );
+    /// ```
+    pub fn probe_termination(&self, type_name: &str) -> bool {
+        self.probe(&format!("fn f<T: ::std::process::Termination>() {{ }} \
+                             fn main() {{ f::<{}>(); }}",
+                            type_name))
+    }
+
+    /// Probes whether the given trait, which may have generic arguments, is
+    /// object safe, so that `dyn Trait` is allowed.
+    ///
+    /// The rules for object safety have been relaxed over time. To find out
+    /// whether a trait of your own would be object safe, give its definition
+    /// to [`probe_item`](#method.probe_item) along with a use of `dyn`, as in
+    /// the last example.
+    ///
+    /// Compilers older than 1.27, which don't know `dyn`, are given a bare
+    /// trait object type instead.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_object_safe("std::fmt::Debug") );
+    /// assert!(   probe.probe_object_safe("Iterator<Item = u8>") );
+    /// assert!( ! probe.probe_object_safe("Clone") );
+    ///
+    /// assert!( probe.probe_item("pub trait T { fn f(self) where Self: Sized; } \
+    ///                            pub fn g(_: &dyn T) { }") );
+    /// ```
+    pub fn probe_object_safe(&self, trait_name: &str) -> bool {
+        let dyn_keyword = if self.rustc_supports(DYN_VERSION) { "dyn " } else { "" };
+        self.probe_items_as_lib(&format!("#[allow(dead_code)] fn check(_: &{}{}) {{ }}",
+                                         dyn_keyword, trait_name))
+    }
+
+    /// Probes whether the given items compile as a library crate.
+    ///
+    /// Use this for items that can't be probed as types or expressions, such
+    /// as unions, traits or extern blocks.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_item("pub union U { a: u32, b: f32 }") );
+    /// assert!( ! probe.probe_item("pub union U { }") );
+    /// ```
+    pub fn probe_item(&self, code: &str) -> bool {
+        self.probe_items_as_lib(code)
+    }
+
+    /// Probes many named groups of items at once, as with
+    /// [`probe_item`](#method.probe_item), returning a report of which of
+    /// them compile.
+    ///
+    /// Each group is wrapped in a module of its own, and the modules are
+    /// compiled together as one library crate. The compiler's errors are
+    /// attributed to modules by their location, and the modules without
+    /// errors are compiled again until no errors remain, so a cheap probe
+    /// costs much less than a compilation of its own. When an error can't be
+    /// attributed, the remaining groups are probed one at a time.
+    ///
+    /// Since each group is in a module, it sees the prelude but not the
+    /// other groups, and any paths through `crate` must name its module,
+    /// which is unspecified. Inner attributes such as `#![feature(...)]`
+    /// only work at the crate root, so a group that starts with `#!` is
+    /// compiled on its own, with the same answer `probe_item` gives.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let report = probe.probe_items_list(&[
+    ///     ("union",       "pub union U { a: u32, b: f32 }"),
+    ///     ("empty_union", "pub union U { }"),
+    ///     ("u512",        "pub fn f(_: u512) { }"),
+    ///     ("i128",        "pub fn f(x: i128) -> i128 { x }"),
+    /// ]).unwrap();
+    ///
+    /// assert_eq!( report.get("union"),       Some(true) );
+    /// assert_eq!( report.get("empty_union"), Some(false) );
+    /// assert_eq!( report.get("u512"),        Some(false) );
+    /// assert_eq!( report.get("i128"),        Some(true) );
+    ///
+    /// let no_std = "#![no_std] pub fn f() -> ::core::option::Option<u8> { None }";
+    /// let report = probe.probe_items_list(&[("no_std", no_std)]).unwrap();
+    /// assert_eq!( report.get("no_std"), Some(probe.probe_item(no_std)) );
+    /// assert_eq!( report.get("no_std"), Some(true) );
+    /// ```
+    pub fn probe_items_list(&self, items: &[(&str, &str)]) -> io::Result<ProbeReport> {
+        let mut probe = self.clone();
+        probe.crate_type = Some("lib");
+
+        let mut results = vec![None; items.len()];
+        let mut pending = Vec::new();
+        for (index, &(_, code)) in items.iter().enumerate() {
+            if compat::trim_start(code).starts_with("#!") {
+                results[index] = Some(probe.probe_result(code)?);
+            } else {
+                pending.push(index);
+            }
+        }
+
+        while !pending.is_empty() {
+            let mut code = String::new();
+            let mut lines = Vec::new();
+            for &index in &pending {
+                let first = code.lines().count() + 1;
+                code.push_str(&format!("mod feature_probe_item_{} {{\n{}\n}}\n",
+                                       index, items[index].1));
+                lines.push((first, code.lines().count(), index));
+            }
+
+            let mut command = String::new();
+            let mut diagnostics = String::new();
+            if probe.compile_capturing(&code, &mut command, &mut diagnostics)? {
+                for &index in &pending {
+                    results[index] = Some(true);
+                }
+                break;
+            }
+
+            let failed = match error_lines(&diagnostics) {
+                Some(ref errors) if !errors.is_empty() => lines.iter()
+                    .filter(|&&(first, last, _)| errors.iter().any(|&line| first <= line && line <= last))
+                    .map(|&(_, _, index)| index)
+                    .collect::<Vec<_>>(),
+                _ => Vec::new(),
+            };
+
+            if failed.is_empty() {
+                for &index in &pending {
+                    results[index] = Some(probe.probe_result(items[index].1)?);
+                }
+                break;
+            }
+
+            for &index in &failed {
+                results[index] = Some(false);
+            }
+            pending.retain(|index| !failed.contains(index));
+        }
+
+        let mut report = ProbeReport::new();
+        for (&(name, _), present) in items.iter().zip(results) {
+            report.record(name, present.unwrap_or(false));
+        }
+        Ok(report)
+    }
+
+    /// Probes whether the type of the given expression satisfies the given
+    /// trait bound, such as `"Send + 'static"` or `"Iterator<Item = u8>"`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_expression_impls("\"text\".chars()", "Iterator<Item = char>") );
+    /// assert!( ! probe.probe_expression_impls("std::rc::Rc::new(0)", "Send") );
+    /// ```
+    pub fn probe_expression_impls(&self, expression: &str, bound: &str) -> bool {
+        self.probe(&format!("fn check<T: {}>(_: T) {{ }} \
+                             fn main() {{ check({{ {} }}); }}",
+                            bound, expression))
+    }
+
+    /// Determines the type that inference picks for the given expression,
+    /// by reading it from the error a deliberate type mismatch provokes, or
+    /// `None` if the expression doesn't compile or the type can't be read.
+    ///
+    /// The expression is assigned to a variable of a type of the probe's
+    /// own, so every type mismatches it. The exception is a diverging
+    /// expression, such as `panic!()`, whose type `!` coerces to any type:
+    /// the program then compiles with nothing to read, which also gives
+    /// `None`.
+    ///
+    /// Types are written as the compiler writes them, with paths in full
+    /// where it gives them. Integer and floating-point literals whose type
+    /// isn't otherwise determined give `{integer}` and `{float}`.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let vec = probe.probe_expression_type_name("vec![1u8]").unwrap().unwrap();
+    /// assert!( vec.ends_with("Vec<u8>") );
+    /// assert_eq!( probe.probe_expression_type_name("1 + 2").unwrap(),
+    ///             Some(String::from("{integer}")) );
+    /// assert_eq!( probe.probe_expression_type_name("()").unwrap(),
+    ///             Some(String::from("()")) );
+    /// assert_eq!( probe.probe_expression_type_name("{ let _x = 1u8; }").unwrap(),
+    ///             Some(String::from("()")) );
+    /// assert_eq!( probe.probe_expression_type_name("3 + true").unwrap(), None );
+    /// assert_eq!( probe.probe_expression_type_name("panic!()").unwrap(), None );
+    /// assert_eq!( probe.probe_expression_type_name("loop { }").unwrap(), None );
+    /// ```
+    pub fn probe_expression_type_name(&self, expression: &str) -> io::Result<Option<String>> {
+        let code = format!("struct {0}; \
+                            #[allow(unreachable_code)] fn main() {{ let _: {0} = {{ {1} }}; }}",
+                           MISMATCHED_TYPE, expression);
+        let mut command = String::new();
+        let mut diagnostics = String::new();
+
+        // Only a diverging expression gets past the mismatch.
+        if self.compile_capturing(&code, &mut command, &mut diagnostics)? {
+            return Ok(None);
+        }
+        if !diagnostics.contains("E0308") {
+            return Ok(None);
+        }
+
+        Ok(mismatched_type(&diagnostics))
+    }
+
+    /// Probes whether the given items compile as a procedural macro crate,
+    /// for detecting additions to the `proc_macro` API.
+    ///
+    /// Procedural macros run in the compiler, so the crate is compiled for
+    /// the host even if this probe has a target. The items must bring in
+    /// `proc_macro` themselves, with `extern crate proc_macro;`, and such
+    /// crates may only export macros, so other items must be private.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_proc_macro("extern crate proc_macro; \
+    ///                                    fn _f() { let _ = proc_macro::Span::call_site; }") );
+    /// assert!( ! probe.probe_proc_macro("extern crate proc_macro; \
+    ///                                    fn _f() { let _ = proc_macro::Span::no_such; }") );
+    /// ```
+    pub fn probe_proc_macro(&self, items: &str) -> bool {
+        let mut probe = self.clone();
+        probe.target = None;
+        probe.crate_type = Some("proc-macro");
+        probe.probe(items)
+    }
+
+    /// Probes whether unions may have fields that aren't `Copy`, wrapped in
+    /// `ManuallyDrop`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if probe.probe_non_copy_union() {
+    ///     println!("cargo:rustc-cfg=non_copy_union");
+    /// }
+    /// ```
+    pub fn probe_non_copy_union(&self) -> bool {
+        self.probe_item("pub union U { a: ::std::mem::ManuallyDrop<String>, b: u32 }")
+    }
+
+    /// Probes whether extern types (`extern { type T; }`) are accepted with
+    /// the `extern_types` feature gate enabled.
+    ///
+    /// No stable release has extern types, so this is true only on nightly
+    /// toolchains. A crate can emit a `cfg` and then write
+    /// `#![cfg_attr(extern_types, feature(extern_types))]` to use them where
+    /// they exist.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_extern_type(&self) -> bool {
+        self.probe_item("#![feature(extern_types)] extern \"C\" { pub type Opaque; }")
+    }
+
+    /// Probes whether an `unsafe trait` with an `unsafe fn` can be declared
+    /// and given an `unsafe impl`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_unsafe_trait() );
+    /// ```
+    pub fn probe_unsafe_trait(&self) -> bool {
+        self.probe_item("pub unsafe trait T { unsafe fn f(&self); } \
+                         unsafe impl T for u8 { unsafe fn f(&self) { } }")
+    }
+
+    /// Probes whether the given unsafe trait, which may have generic
+    /// arguments, can be implemented with an empty `unsafe impl` for a local
+    /// type.
+    ///
+    /// This is false for traits that aren't unsafe and for traits with
+    /// required items.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_unsafe_impl("Send") );
+    /// assert!(   probe.probe_unsafe_impl("Sync") );
+    /// assert!( ! probe.probe_unsafe_impl("Clone") );
+    /// assert!( ! probe.probe_unsafe_impl("std::alloc::GlobalAlloc") );
+    /// ```
+    pub fn probe_unsafe_impl(&self, trait_name: &str) -> bool {
+        self.probe_item(&format!("pub struct FeatureProbeLocal; \
+                                  unsafe impl {} for FeatureProbeLocal {{ }}",
+                                 trait_name))
+    }
+
+    /// Probes whether extern blocks may be declared `unsafe extern`, with
+    /// items marked `safe`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_unsafe_extern_block(&self) -> bool {
+        self.probe_item("unsafe extern \"C\" { pub safe fn abs(x: i32) -> i32; }")
+    }
+
+    /// Probes whether unsafe attributes may be written `#[unsafe(...)]`, as
+    /// in `#[unsafe(no_mangle)]`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_unsafe_attribute(&self) -> bool {
+        self.probe_item("#[unsafe(no_mangle)] pub extern \"C\" fn feature_probe_f() { }")
+    }
+
+    /// Probes whether the given `#[doc(...)]` attribute is accepted on an
+    /// item, without any feature gate.
+    ///
+    /// Unknown attributes are caught by the `invalid_doc_attributes` lint,
+    /// which Rust 1.53 introduced. Older compilers accept any attribute, so
+    /// they can't tell, and the probe answers `false` on them.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_doc_attribute("hidden") );
+    /// assert!( ! probe.probe_doc_attribute("no_such_attribute") );
+    /// ```
+    pub fn probe_doc_attribute(&self, attribute: &str) -> bool {
+        self.probe_item(&format!("#![deny(unknown_lints, invalid_doc_attributes)] \
+                                  #[doc({})] pub fn f() {{ }}",
+                                 attribute))
+    }
+
+    /// Probes whether `#[doc(cfg(...))]` is accepted with the `doc_cfg`
+    /// feature gate enabled.
+    ///
+    /// This is typically true only on nightly toolchains, such as the one
+    /// docs.rs uses, so a crate can emit a `cfg` and then write
+    /// `#![cfg_attr(doc_cfg, feature(doc_cfg))]` and
+    /// `#[cfg_attr(doc_cfg, doc(cfg(...)))]` without breaking stable builds.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_doc_cfg(&self) -> bool {
+        self.probe_item("#![feature(doc_cfg)] #[doc(cfg(unix))] pub fn f() { }")
+    }
+
+    /// Probes whether the `doc_auto_cfg` feature gate can be enabled.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_doc_auto_cfg(&self) -> bool {
+        self.probe_item("#![feature(doc_auto_cfg)] pub fn f() { }")
+    }
+
+    /// Probes whether `#[doc(alias = "...")]` is accepted.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_doc_alias(&self) -> bool {
+        self.probe_doc_attribute("alias = \"feature_probe_alias\"")
+    }
+
+    /// Probes whether the given raw identifier, such as `r#try`, can be used
+    /// as a variable and field name.
+    ///
+    /// The `r#` prefix is added if missing. Some keywords, such as `self`
+    /// and `crate`, can't be raw identifiers at all.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_raw_identifier("r#try") );
+    /// assert!( ! probe.probe_raw_identifier("r#self") );
+    /// ```
+    pub fn probe_raw_identifier(&self, ident: &str) -> bool {
+        let ident = if ident.starts_with("r#") {
+            ident.to_owned()
+        } else {
+            format!("r#{}", ident)
+        };
+
+        self.probe_identifier(&ident)
+    }
+
+    /// Probes whether the given identifier is a keyword, reserved or in use,
+    /// for the configured [`edition`](#method.edition): that is, whether it
+    /// can't be used as a plain variable and field name.
+    ///
+    /// Code generators can use this to decide when to emit a raw identifier.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// assert!( ! probe.probe_keyword("async") );
+    /// assert!(   probe.probe_keyword("match") );
+    ///
+    /// probe.edition("2018");
+    /// assert!(   probe.probe_keyword("async") );
+    /// ```
+    pub fn probe_keyword(&self, ident: &str) -> bool {
+        !self.probe_identifier(ident)
+    }
+
+    fn probe_identifier(&self, ident: &str) -> bool {
+        self.probe(&format!("struct S {{ {0}: u8 }} \
+                             fn main() {{ let {0} = 0; let s = S {{ {0}: {0} }}; let _ = s.{0}; }}",
+                            ident))
+    }
+}
+
+/// Reads the found type from the diagnostics for a mismatch with
+/// `MISMATCHED_TYPE`, ignoring any other mismatches in the expression.
+fn mismatched_type(diagnostics: &str) -> Option<String> {
+    let expected = format!("`{}`", MISMATCHED_TYPE);
+    let lines = diagnostics.lines().map(str::trim).collect::<Vec<_>>();
+
+    // The note, where there is one, has paths in full.
+    let note = lines.windows(2)
+        .find(|pair| pair[0].ends_with(&expected)
+                     && pair[1].starts_with("found ") && pair[1].ends_with('`'));
+    if let Some(pair) = note {
+        return quoted(pair[1]);
+    }
+
+    let label = ", found ";
+    let found = try_opt!(lines.iter()
+        .filter(|line| line.contains(&expected))
+        .filter_map(|line| line.find(label).map(|start| line[start + label.len() ..].trim()))
+        .next());
+
+    if found.starts_with('`') {
+        quoted(found)
+    } else if found == "integer" {
+        Some(String::from("{integer}"))
+    } else if found == "floating-point number" {
+        Some(String::from("{float}"))
+    } else {
+        None
+    }
+}
+
+/// The text between the first and last backquotes.
+fn quoted(text: &str) -> Option<String> {
+    let start = try_opt!(text.find('`'));
+    let end = try_opt!(text.rfind('`'));
+    if end > start {
+        Some(text[start + 1 .. end].to_owned())
+    } else {
+        None
+    }
+}
+
+/// The lines of the probe program where the compiler's errors are, or
+/// `None` if some error isn't located in the program.
+fn error_lines(diagnostics: &str) -> Option<Vec<usize>> {
+    let mut lines = Vec::new();
+    let mut in_error = false;
+    let mut located = true;
+
+    for line in diagnostics.lines() {
+        if line.starts_with("error") {
+            if !located {
+                return None;
+            }
+            // Summaries such as "aborting due to 2 previous errors" have no
+            // location of their own.
+            in_error = !line.starts_with("error: aborting due to");
+            located = !in_error;
+        } else if line.starts_with("warning") {
+            if !located {
+                return None;
+            }
+            in_error = false;
+        } else if in_error && !located {
+            let location = compat::trim_start(line);
+            if location.starts_with("--> ") {
+                let mut parts = location[4 ..].rsplitn(3, ':');
+                let _column = parts.next();
+                let number  = parts.next().and_then(|number| number.parse().ok());
+                let file    = parts.next().unwrap_or("");
+                match number {
+                    Some(number) if file == "<anon>" || file.ends_with(INPUT_FILE) => {
+                        lines.push(number);
+                        located = true;
+                    }
+                    _ => return None,
+                }
+            }
+        }
+    }
+
+    if located { Some(lines) } else { None }
+}
@@ -0,0 +1,193 @@
+use std::env;
+#[cfg(feature = "jobserver")]
+use std::io;
+use std::ptr;
+use std::sync::{Condvar, Mutex, MutexGuard, Once};
+
+#[cfg(feature = "jobserver")]
+use jobserver;
+
+/// Limits how many probes run at once across the process, so that probing
+/// concurrently cooperates with the rest of the build.
+///
+/// With the `jobserver` feature, under a Cargo that provides a jobserver, one
+/// probe at a time uses the build script's own job and any others wait for
+/// jobs from the jobserver. Otherwise, at most `NUM_JOBS` (which Cargo sets
+/// for build scripts) or `CARGO_BUILD_JOBS` probes run at once, or any number
+/// if neither is set.
+struct Limiter {
+    state:  Mutex<State>,
+    freed:  Condvar,
+    limit:  Option<usize>,
+    #[cfg(feature = "jobserver")]
+    helper: Option<jobserver::HelperThread>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Probes running within the limit, rather than on a jobserver token.
+    running: usize,
+    /// Probes waiting for a token they have requested.
+    #[cfg(feature = "jobserver")]
+    waiting: usize,
+    /// Tokens that have arrived for waiting probes.
+    #[cfg(feature = "jobserver")]
+    tokens:  Vec<jobserver::Acquired>,
+}
+
+/// Permission to run a probe, which lasts until it is dropped.
+pub struct Slot {
+    #[cfg(feature = "jobserver")]
+    token: Option<jobserver::Acquired>,
+}
+
+impl Slot {
+    #[cfg(feature = "jobserver")]
+    fn within_limit() -> Self {
+        Slot { token: None }
+    }
+
+    #[cfg(not(feature = "jobserver"))]
+    fn within_limit() -> Self {
+        Slot { }
+    }
+
+    #[cfg(feature = "jobserver")]
+    fn is_within_limit(&self) -> bool {
+        self.token.is_none()
+    }
+
+    #[cfg(not(feature = "jobserver"))]
+    fn is_within_limit(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        // A token goes back to the jobserver when it is dropped.
+        if self.is_within_limit() {
+            let limiter = limiter();
+            limiter.state.lock().unwrap().running -= 1;
+            limiter.freed.notify_all();
+        }
+    }
+}
+
+/// Waits until a probe may run.
+pub fn acquire() -> Slot {
+    let limiter = limiter();
+    let mut state = limiter.state.lock().unwrap();
+    let mut requested = false;
+
+    loop {
+        if limiter.limit.map_or(true, |limit| state.running < limit) {
+            state.running += 1;
+            finish_waiting(&mut state, requested);
+            return Slot::within_limit();
+        }
+
+        if let Some(slot) = take_token(limiter, &mut state, &mut requested) {
+            return slot;
+        }
+
+        state = limiter.freed.wait(state).unwrap();
+    }
+}
+
+/// Takes a token that has arrived, or requests one if there's a jobserver.
+#[cfg(feature = "jobserver")]
+fn take_token(limiter: &Limiter, state: &mut MutexGuard<State>, requested: &mut bool)
+              -> Option<Slot> {
+    if *requested {
+        if let Some(token) = state.tokens.pop() {
+            finish_waiting(state, true);
+            return Some(Slot { token: Some(token) });
+        }
+    } else if let Some(ref helper) = limiter.helper {
+        helper.request_token();
+        state.waiting += 1;
+        *requested = true;
+    }
+
+    None
+}
+
+#[cfg(not(feature = "jobserver"))]
+fn take_token(_limiter: &Limiter, _state: &mut MutexGuard<State>, _requested: &mut bool)
+              -> Option<Slot> {
+    None
+}
+
+#[cfg(feature = "jobserver")]
+fn finish_waiting(state: &mut State, requested: bool) {
+    // The token requested may still arrive; it is then passed on to another
+    // waiting probe or given back.
+    if requested {
+        state.waiting -= 1;
+    }
+}
+
+#[cfg(not(feature = "jobserver"))]
+fn finish_waiting(_state: &mut State, _requested: bool) { }
+
+#[clippy::msrv = "1.36.0"]
+fn limiter() -> &'static Limiter {
+    static INIT: Once = Once::new();
+    static mut LIMITER: *const Limiter = ptr::null();
+
+    // Safety: `LIMITER` is written once, under `INIT`, before any read, and
+    // the limiter it points to is never freed.
+    unsafe {
+        INIT.call_once(|| {
+            LIMITER = Box::into_raw(Box::new(new_limiter()));
+        });
+        &*LIMITER
+    }
+}
+
+#[cfg(feature = "jobserver")]
+fn new_limiter() -> Limiter {
+    // Safety: this is called once, and the jobserver's file descriptors are
+    // only used through this client.
+    let client = unsafe { jobserver::Client::from_env() };
+    let helper = client.and_then(|client| client.into_helper_thread(receive_token).ok());
+
+    Limiter {
+        state:  Mutex::new(State::default()),
+        freed:  Condvar::new(),
+        // The build script's own job lets one probe run without a token.
+        limit:  if helper.is_some() { Some(1) } else { jobs_from_env() },
+        helper,
+    }
+}
+
+#[cfg(feature = "jobserver")]
+fn receive_token(token: io::Result<jobserver::Acquired>) {
+    // Waiting probes still get to run once the build script's own job is
+    // free, so a failure to get a token is not fatal.
+    if let Ok(token) = token {
+        let limiter = limiter();
+        let mut state = limiter.state.lock().unwrap();
+        if state.tokens.len() < state.waiting {
+            state.tokens.push(token);
+            limiter.freed.notify_all();
+        }
+    }
+}
+
+#[cfg(not(feature = "jobserver"))]
+fn new_limiter() -> Limiter {
+    Limiter {
+        state: Mutex::new(State::default()),
+        freed: Condvar::new(),
+        limit: jobs_from_env(),
+    }
+}
+
+fn jobs_from_env() -> Option<usize> {
+    ["NUM_JOBS", "CARGO_BUILD_JOBS"].iter()
+        .filter_map(|var| env::var(var).ok())
+        .filter_map(|jobs| jobs.trim().parse().ok())
+        .find(|&jobs| jobs > 0)
+}
@@ -0,0 +1,79 @@
+use std::io;
+use std::sync::Mutex;
+
+use super::Probe;
+
+/// A probe that isn't carried out until its answer is needed.
+///
+/// Create one with [`Probe::lazy_probe`](struct.Probe.html#method.lazy_probe).
+/// The first call to [`get`](#method.get) compiles the program, and later
+/// calls return the remembered answer.
+#[derive(Debug)]
+pub struct LazyProbe {
+    probe:  Probe,
+    code:   String,
+    answer: Mutex<Option<bool>>,
+}
+
+impl LazyProbe {
+    /// Whether the program compiles, probing for it if that hasn't been done
+    /// yet.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn get(&self) -> bool {
+        self.get_result().expect("LazyProbe::get")
+    }
+
+    /// Whether the program compiles, probing for it if that hasn't been done
+    /// yet. Errors are not remembered, so a later call probes again.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn get_result(&self) -> io::Result<bool> {
+        let mut answer = self.answer.lock().unwrap();
+        if let Some(present) = *answer {
+            return Ok(present);
+        }
+
+        let present = self.probe.probe_result(&self.code)?;
+        *answer = Some(present);
+        Ok(present)
+    }
+
+    /// Whether the probe has been carried out.
+    pub fn is_evaluated(&self) -> bool {
+        self.answer.lock().unwrap().is_some()
+    }
+}
+
+impl Probe {
+    /// Returns a probe for whether a whole program can be compiled that is
+    /// only carried out when its answer is first needed.
+    ///
+    /// The lazy probe uses a copy of this probe's current configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let i128 = probe.lazy_probe("fn main() { let _: i128 = 0; }");
+    /// assert_eq!( probe.stats().compilations, 0 );
+    ///
+    /// assert!( i128.get() );
+    /// assert!( i128.get() );
+    /// assert!( i128.is_evaluated() );
+    /// assert_eq!( probe.stats().compilations, 1 );
+    /// ```
+    pub fn lazy_probe(&self, code: &str) -> LazyProbe {
+        LazyProbe {
+            probe:  self.clone(),
+            code:   code.to_owned(),
+            answer: Mutex::new(None),
+        }
+    }
+}
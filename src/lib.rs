@@ -40,21 +40,226 @@
 //!
 //! This crate supports Rust version 1.16.0 and later.
 
+// `dyn` would require Rust 1.27.
+#![allow(bare_trait_objects)]
+
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::ffi::OsString;
-use std::io::{self, Write};
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "jobserver")]
+extern crate jobserver;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+/// Unwraps an option or returns `None`, as `?` on an option does from Rust
+/// 1.22.
+macro_rules! try_opt {
+    ($e:expr) => (match $e { Some(x) => x, None => return None })
+}
+
+mod allocator;
+mod async_support;
+mod backend;
+mod builder;
+mod cache;
+mod cancel;
+mod codegen;
+mod compat;
+mod const_generics;
+mod context;
+mod crate_types;
+mod diff;
+mod event;
+mod explain;
+mod fingerprint;
+mod features;
+#[cfg(feature = "async")]
+mod future;
+mod golden;
+mod harness;
+#[cfg(feature = "async")]
+mod jobs;
+mod inspect;
+mod items;
+mod lazy;
+mod limits;
+mod linker;
+mod lints;
+mod manifest;
+mod matrix;
+mod native;
+mod options;
+mod outcome;
+mod policy;
+mod profile;
+mod progress;
+mod report;
+mod reporter;
+mod require;
+mod run;
+mod self_check;
+mod shared;
+mod spawn;
+#[cfg(feature = "stabilization-db")]
+mod stabilization;
+mod stats;
+mod sweep;
+mod syntax;
+mod sysroot;
+mod table;
+mod usable;
+mod temp;
+mod text;
+mod toolchain;
+mod version;
+mod workspace;
+pub use async_support::AsyncSupport;
+pub use backend::{Compilation, ProbeBackend, RustcBackend, ScriptedBackend};
+pub use builder::ProbeBuilder;
+pub use cache::CacheStats;
+pub use cancel::CancellationToken;
+pub use codegen::CodegenBackend;
+pub use const_generics::ConstGenericsTier;
+pub use context::BuildContext;
+pub use crate_types::CRATE_TYPES;
+pub use diff::DiffReport;
+pub use event::ProbeEvent;
+pub use features::TargetFeature;
+#[cfg(feature = "async")]
+pub use future::ProbeFuture;
+pub use golden::assert_golden;
+pub use harness::TypeHarness;
+pub use lazy::LazyProbe;
+pub use linker::LinkerFlavor;
+pub use matrix::ProbeMatrix;
+pub use options::ProbeOptions;
+pub use outcome::ProbeOutcome;
+pub use policy::{ToolchainPolicy, UnavailablePolicy};
+pub use profile::CompilerProfile;
+pub use progress::Progress;
+pub use report::{ProbeReport, ReportIter};
+pub use reporter::{RecordingReporter, Reporter, StdoutReporter};
+pub use run::RunOutcome;
+pub use stats::ProbeStats;
+pub use syntax::SyntaxFeature;
+pub use table::SupportTable;
+pub use usable::Usability;
+pub use version::Version;
+pub use workspace::workspace_results_path;
 
 /// A probe object, which is used for probing for features.
 ///
 /// Create this with [`Probe::new`](#method.new), and then probe with
 /// one of the probing methods.
-#[derive(Debug)]
+#[derive(Clone)]
 pub struct Probe {
-    rustc:   OsString,
-    out_dir: OsString,
+    rustc:         OsString,
+    out_dir:       OsString,
+    target:        Option<OsString>,
+    edition:       Option<String>,
+    crate_type:    Option<&'static str>,
+    emit:          String,
+    cfg_prefix:    String,
+    args:          Vec<OsString>,
+    envs:          BTreeMap<OsString, OsString>,
+    hardened:      bool,
+    env_allowlist: BTreeSet<OsString>,
+    sanitize_env:  bool,
+    timeout:       Option<Duration>,
+    limits:        limits::ResourceLimits,
+    runner:        Option<Vec<OsString>>,
+    backend:       Arc<ProbeBackend>,
+    reporter:      Arc<Reporter>,
+    before_spawn:  Option<Arc<Mutex<SpawnHook>>>,
+    observer:      Option<Arc<Observer>>,
+    progress:      Option<Arc<progress::Tracker>>,
+    cancel:        Option<CancellationToken>,
+    label:         Option<String>,
+    wrapper:       Vec<OsString>,
+    profile:       Option<CompilerProfile>,
+    context:       Option<BuildContext>,
+    answers:       Vec<(BuildContext, bool)>,
+    unavailable:   UnavailablePolicy,
+    mismatch:      ToolchainPolicy,
+    cache_file:    Option<PathBuf>,
+    cache_rerun:   Arc<AtomicBool>,
+    share_answers: bool,
+    auto_check:    bool,
+    rustc_info:    Arc<Mutex<Option<RustcInfo>>>,
+    stats:         Arc<Mutex<ProbeStats>>,
 }
 
+/// The output of `rustc -vV`, along with the command that produced it.
+type RustcInfo = (Vec<OsString>, String);
+
+/// A function that adjusts a probe's command before it runs.
+type SpawnHook = FnMut(&mut Command) + Send;
+
+/// A function that is told about every probe.
+type Observer = Fn(&ProbeEvent) + Send + Sync;
+
+/// The output kinds `rustc --emit` accepts.
+const EMIT_KINDS: &'static [&'static str] = &[
+    "asm", "dep-info", "link", "llvm-bc", "llvm-ir", "metadata", "mir", "obj",
+];
+
+/// The file probe programs are written to for compilers that can't read
+/// them from standard input.
+const INPUT_FILE: &'static str = "probe.rs";
+
+/// The name of the cache file in a directory given to `Probe::cache_dir`.
+const CACHE_FILE_NAME: &'static str = "feature-probe.cache";
+
+/// Environment variables that select the compiler or its flags, so that a
+/// build script with a cache reruns when they change.
+const CACHE_ENV_INPUTS: &'static [&'static str] = &[
+    "RUSTC", "RUSTC_WRAPPER", "RUSTC_WORKSPACE_WRAPPER", "RUSTUP_TOOLCHAIN",
+    "CARGO_ENCODED_RUSTFLAGS",
+];
+
+/// What a `no_std` executable compiled with `-C panic=abort` needs besides
+/// the probe program.
+const NO_STD_STUBS: &'static str = "#[panic_handler] \
+    fn feature_probe_panic(_: &core::panic::PanicInfo) -> ! { loop { } }";
+
+/// The first release that accepts `--check-cfg` on stable.
+const CHECK_CFG_VERSION: Version = Version { major: 1, minor: 80, patch: 0 };
+
+/// The first release that accepts `--edition`.
+const EDITION_FLAG_VERSION: Version = Version { major: 1, minor: 31, patch: 0 };
+
+/// Environment variables passed to `rustc` in hardened mode by default.
+const DEFAULT_ENV_ALLOWLIST: &'static [&'static str] = &[
+    "PATH", "HOME", "USERPROFILE", "SYSTEMROOT", "TEMP", "TMP", "TMPDIR",
+    "RUSTUP_HOME", "RUSTUP_TOOLCHAIN", "CARGO_HOME",
+    "LD_LIBRARY_PATH", "DYLD_LIBRARY_PATH", "DYLD_FALLBACK_LIBRARY_PATH",
+];
+
+/// Environment variables removed from `rustc`'s environment unless
+/// `Probe::sanitize_env` is turned off, since they slow probes down or
+/// change their output without changing their answers.
+const SANITIZED_ENV: &'static [&'static str] = &[
+    "CARGO_INCREMENTAL", "RUSTC_LOG", "RUSTC_LOG_COLOR", "RUST_LOG", "RUSTC_ICE",
+    "COLUMNS", "LINES",
+];
+
+/// Environment variables set for `rustc` unless `Probe::sanitize_env` is
+/// turned off, so that the linker and other tools it runs write the same
+/// diagnostics on every machine.
+const NORMALIZED_ENV: &'static [(&'static str, &'static str)] = &[
+    ("LC_ALL", "C"), ("LANG", "C"),
+];
+
 impl Probe {
     /// Creates a new [`Probe`](struct.Probe.html) object with a default
     /// configuration.
@@ -78,102 +283,2533 @@ impl Probe {
     /// ```
     pub fn new() -> Self {
         Probe {
-            rustc:   env_var_or("RUSTC",   "rustc"),
-            out_dir: env_var_or("OUT_DIR", "target"),
+            rustc:         env_var_or("RUSTC",   "rustc"),
+            out_dir:       env_var_or("OUT_DIR", "target"),
+            target:        None,
+            edition:       manifest::edition(),
+            crate_type:    None,
+            emit:          String::from("obj"),
+            cfg_prefix:    String::new(),
+            args:          Vec::new(),
+            envs:          BTreeMap::new(),
+            hardened:      false,
+            env_allowlist: DEFAULT_ENV_ALLOWLIST.iter().map(OsString::from).collect(),
+            sanitize_env:  true,
+            timeout:       None,
+            limits:        limits::ResourceLimits::default(),
+            runner:        None,
+            backend:       Arc::new(RustcBackend),
+            reporter:      Arc::new(StdoutReporter),
+            before_spawn:  None,
+            observer:      None,
+            progress:      None,
+            cancel:        None,
+            label:         None,
+            wrapper:       Vec::new(),
+            profile:       None,
+            context:       None,
+            answers:       Vec::new(),
+            unavailable:   UnavailablePolicy::Panic,
+            mismatch:      ToolchainPolicy::Warn,
+            cache_file:    None,
+            cache_rerun:   Arc::new(AtomicBool::new(false)),
+            share_answers: false,
+            auto_check:    false,
+            rustc_info:    Arc::new(Mutex::new(None)),
+            stats:         Arc::new(Mutex::new(ProbeStats::default())),
         }
     }
 
-    /// Probes for the existence of the given type by name.
+    /// Starts building a probe by value, as an alternative to configuring one
+    /// in place.
     ///
-    /// # Panics
+    /// # Examples
     ///
-    /// If the child `rustc` cannot be started or communicated with.
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::builder().edition("2018").build();
+    /// assert!( probe.probe_expression("async { }") );
+    /// ```
+    pub fn builder() -> ProbeBuilder {
+        ProbeBuilder::new()
+    }
+
+    /// Returns a copy of this probe that compiles for the host, for answering
+    /// questions about code that runs at build time, such as procedural
+    /// macros and build dependencies.
+    ///
+    /// The copy has no target set and labels the `cfg`s it emits with the
+    /// prefix `host_`, so that they don't collide with the answers for the
+    /// target. See [`cfg_prefix`](#method.cfg_prefix).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, RecordingReporter};
+    ///
+    /// let recorder = RecordingReporter::new();
+    /// let mut probe = Probe::new();
+    /// probe.reporter(recorder.clone());
+    ///
+    /// let host   = probe.for_host();
+    /// let target = probe.for_target();
+    /// host.emit_cfg("i128");
+    /// target.emit_cfg("i128");
+    /// assert_eq!( recorder.cfgs(), vec!["host_i128", "i128"] );
+    /// ```
+    pub fn for_host(&self) -> Probe {
+        let mut probe = self.clone();
+        probe.target = None;
+        probe.cfg_prefix = String::from("host_");
+        probe
+    }
+
+    /// Returns a copy of this probe whose probes are named, so that an
+    /// [`observer`](#method.observer) can tell them apart.
+    pub fn named<S: Into<String>>(&self, name: S) -> Probe {
+        let mut probe = self.clone();
+        probe.label = Some(name.into());
+        probe
+    }
+
+    /// Returns a copy of this probe that compiles for the target of the
+    /// current build, as given by the `"TARGET"` environment variable that
+    /// Cargo passes to build scripts, and emits `cfg`s unlabeled.
+    ///
+    /// If `"TARGET"` isn't set, the copy keeps this probe's target.
+    pub fn for_target(&self) -> Probe {
+        let mut probe = self.clone();
+        if let Some(target) = env::var_os("TARGET") {
+            probe.target = Some(target);
+        }
+        probe.cfg_prefix = String::new();
+        probe
+    }
+
+    /// Sets a prefix for the names of the `cfg`s emitted through this probe.
+    ///
+    /// This applies to [`emit_cfg`](#method.emit_cfg),
+    /// [`emit_report_cfgs`](#method.emit_report_cfgs) and the methods built
+    /// on them, but not to the free function [`emit_cfg`](fn.emit_cfg.html)
+    /// or to reports emitted directly.
+    pub fn cfg_prefix<S: Into<String>>(&mut self, prefix: S) -> &mut Self {
+        self.cfg_prefix = prefix.into();
+        self
+    }
+
+    /// Sets a command to run `rustc` through, such as `docker run …` or
+    /// `ssh host`, for probing with a compiler that isn't installed
+    /// locally.
+    ///
+    /// The first word is the program; the rest are arguments, which come
+    /// before the path of `rustc` and its own arguments. The wrapper is also
+    /// used to determine the compiler's version. Paths in `rustc`'s
+    /// arguments, such as the output directory, are passed unchanged, so the
+    /// wrapper must make them valid where the compiler runs.
     ///
     /// # Examples
     ///
     /// ```
     /// use feature_probe::Probe;
     ///
-    /// let probe = Probe::new();
+    /// let mut probe = Probe::new();
+    /// probe.wrapper_command(&["env", "RUST_BACKTRACE=0"]);
+    /// assert!( probe.probe_type("u32") );
+    /// ```
+    pub fn wrapper_command<I>(&mut self, wrapper: I) -> &mut Self
+        where I: IntoIterator,
+              I::Item: Into<OsString>
+    {
+        self.wrapper = wrapper.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the kind of compiler probes are compiled with, overriding
+    /// [`CompilerProfile::detect`](enum.CompilerProfile.html#method.detect).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{CompilerProfile, Probe};
+    ///
+    /// let mut probe = Probe::new();
+    /// assert_eq!( probe.profile(), CompilerProfile::Rustc );
+    /// probe.compiler_profile(CompilerProfile::ClippyDriver);
+    /// assert_eq!( probe.profile(), CompilerProfile::ClippyDriver );
+    /// ```
+    pub fn compiler_profile(&mut self, profile: CompilerProfile) -> &mut Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// The kind of compiler probes are compiled with, as set with
+    /// [`compiler_profile`](#method.compiler_profile) or else detected from
+    /// the compiler's name.
+    pub fn profile(&self) -> CompilerProfile {
+        self.profile.unwrap_or_else(|| CompilerProfile::detect(&self.rustc))
+    }
+
+    /// Probes with the `rustc` of the given rustup toolchain, such as
+    /// `"1.56.0"` or `"nightly"`, instead of the default.
+    ///
+    /// This asks rustup where the toolchain's `rustc` is and uses that path,
+    /// so the toolchain must already be installed; it is never installed
+    /// automatically.
+    ///
+    /// Since this picks a different toolchain on purpose, it turns off the
+    /// [toolchain mismatch check](#method.on_toolchain_mismatch).
+    ///
+    /// # Errors
+    ///
+    /// If rustup cannot be run (`ErrorKind::NotFound` if it isn't
+    /// installed) or the toolchain isn't installed, in which case the probe
+    /// is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// assert!( probe.toolchain("no-such-toolchain").is_err() );
+    /// ```
+    pub fn toolchain(&mut self, toolchain: &str) -> io::Result<&mut Self> {
+        let output = Command::new("rustup")
+            .args(&["which", "rustc", "--toolchain", toolchain])
+            .env("RUSTUP_AUTO_INSTALL", "0")
+            .env("RUST_BACKTRACE", "0")
+            .output()
+            .map_err(|e| io::Error::new(e.kind(),
+                                        format!("cannot run rustup to find toolchain {}: {}",
+                                                toolchain, e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      format!("toolchain {} is unavailable: {}",
+                                              toolchain,
+                                              stderr.lines().next().unwrap_or("").trim())));
+        }
+
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        self.rustc = OsString::from(path);
+        self.mismatch = ToolchainPolicy::Ignore;
+        Ok(self)
+    }
+
+    /// Sets the target triple to compile probes for.
+    ///
+    /// By default probes are compiled for the host. In a build script, pass
+    /// the value of the `"TARGET"` environment variable to get answers for the
+    /// target the crate is being built for.
+    ///
+    /// If `rustc` doesn't know the target, or the target's standard library
+    /// isn't installed, probes fail with an error rather than reporting
+    /// features as absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOutcome};
+    ///
+    /// let mut probe = Probe::new();
+    /// if let Some(target) = std::env::var_os("TARGET") {
+    ///     probe.target(target);
+    /// }
+    /// assert!( probe.probe_type("u32") );
+    ///
+    /// probe.target("no-such-target");
+    /// assert_eq!( probe.probe_outcome("fn main() { }"), ProbeOutcome::Unknown );
+    /// ```
+    pub fn target<S: Into<OsString>>(&mut self, target: S) -> &mut Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Sets the edition to compile probes with, such as `"2018"`.
+    ///
+    /// In a build script, probes are compiled by default with the edition of
+    /// the crate being built, as given in its Cargo.toml (directly or
+    /// inherited from the workspace), so that they see the same syntax as
+    /// the crate. Otherwise, or if the manifest doesn't give an edition, no
+    /// `--edition` flag is passed, so `rustc` uses the 2015 edition. This
+    /// method overrides either default. Compilers older than Rust 1.31 don't accept the flag at all,
+    /// so it is left out for them, and they compile probes as the only
+    /// edition they know.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.edition("2018");
+    /// assert!( probe.probe_expression("async { }") );
+    /// ```
+    pub fn edition<S: Into<String>>(&mut self, edition: S) -> &mut Self {
+        self.edition = Some(edition.into());
+        self
+    }
+
+    /// Sets what `rustc` emits for each probe, as a comma-separated list of
+    /// kinds accepted by `--emit`, such as `"metadata"` or `"obj,llvm-ir"`.
+    ///
+    /// The default is `"obj"`, which runs code generation without linking.
+    /// `"metadata"` stops after type checking, which is faster but misses
+    /// errors that only arise later, such as from monomorphization.
+    ///
+    /// # Errors
+    ///
+    /// If the list is empty or names a kind `rustc` doesn't know, in which
+    /// case the probe is left unchanged. Checking here keeps a typo from
+    /// making every probe fail as though its feature were absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.emit("metadata").unwrap();
+    /// assert!( probe.probe_type("u32") );
+    ///
+    /// assert!( probe.emit("objekt").is_err() );
+    /// assert!( probe.emit(format!("{},{}", "obj", "llvm-ir")).is_ok() );
+    /// ```
+    pub fn emit<S: Into<String>>(&mut self, kinds: S) -> io::Result<&mut Self> {
+        let kinds = kinds.into();
+
+        for kind in kinds.split(',') {
+            if !EMIT_KINDS.contains(&kind) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          format!("unknown emit kind {:?}", kind)));
+            }
+        }
+
+        self.emit = kinds;
+        Ok(self)
+    }
+
+    /// Turns hardened mode on or off.
+    ///
+    /// In hardened mode probe results depend only on the compiler, the target
+    /// and the probe program, not on ambient state:
+    ///
+    ///   - `rustc` runs with its environment cleared down to an allowlist of
+    ///     variables needed to find and run the compiler (see
+    ///     [`allow_env`](#method.allow_env));
+    ///   - it runs in, and writes its output to, a fresh empty temporary
+    ///     directory, which is removed afterward;
+    ///   - `--crate-name` and (where supported) `--edition` are passed
+    ///     explicitly, with the edition defaulting to 2015; and
+    ///   - a compiler whose file name isn't `rustc`, such as a caching
+    ///     wrapper, or a [`wrapper_command`](#method.wrapper_command) is
+    ///     refused with an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.hardened(true);
     /// assert!(   probe.probe_type("u32") );
     /// assert!( ! probe.probe_type("u512") );
+    ///
+    /// probe.wrapper_command(&["env", "RUST_BACKTRACE=0"]);
+    /// assert!(   probe.check().is_err() );
+    /// assert!(   probe.probe_result("fn main() { }").is_err() );
+    /// assert!(   probe.probe_files(&[("main.rs", "fn main() { }")]).is_err() );
+    /// assert!(   probe.probe_items_list(&[("unit", "pub struct Unit;")]).is_err() );
+    /// assert!(   probe.probe_expression_type_name("1u8").is_err() );
     /// ```
-    pub fn probe_type(&self, type_name: &str) -> bool {
-        self.probe(&format!("pub type T = {}; fn main() {{ }}", type_name))
+    ///
+    /// Every kind of probe refuses such a compiler, not only
+    /// [`probe_result`](#method.probe_result):
+    ///
+    /// ```
+    /// use std::io;
+    /// use std::panic::{self, AssertUnwindSafe};
+    /// use feature_probe::Probe;
+    ///
+    /// fn refused<T>(result: io::Result<T>) -> bool {
+    ///     result.err().map(|e| e.kind()) == Some(io::ErrorKind::InvalidInput)
+    /// }
+    ///
+    /// fn panics_refusing<T, F: FnOnce() -> T>(f: F) -> bool {
+    ///     match panic::catch_unwind(AssertUnwindSafe(f)) {
+    ///         Ok(_)      => false,
+    ///         Err(cause) => cause.downcast_ref::<String>()
+    ///                            .map_or(false, |message| message.contains("refuses")),
+    ///     }
+    /// }
+    ///
+    /// std::env::set_var("RUSTC", "/no/such/cc");
+    /// let mut probe = Probe::new();
+    /// std::env::remove_var("RUSTC");
+    /// probe.hardened(true);
+    ///
+    /// assert!( refused(probe.check()) );
+    /// assert!( refused(probe.probe_result("fn main() { }")) );
+    /// assert!( refused(probe.probe_files(&[("main.rs", "fn main() { }")])) );
+    /// assert!( refused(probe.probe_items_list(&[("unit", "pub struct Unit;")])) );
+    /// assert!( refused(probe.probe_expression_type_name("1u8")) );
+    /// assert!( refused(probe.supported_crate_types()) );
+    /// assert!( refused(probe.emitted("fn main() { }", "asm")) );
+    /// assert!( refused(probe.probe_run("fn main() { }")) );
+    /// assert!( refused(probe.sysroot()) );
+    /// assert!( probe.explain("fn main() { }").contains("refuses") );
+    ///
+    /// panic::set_hook(Box::new(|_| ()));
+    /// assert!( panics_refusing(|| probe.probe_codegen_flag("-Copt-level=2")) );
+    /// assert!( panics_refusing(|| probe.probe_sanitizer("address")) );
+    /// assert!( panics_refusing(|| probe.probe_link_arg("-Wl,--as-needed")) );
+    /// ```
+    pub fn hardened(&mut self, hardened: bool) -> &mut Self {
+        self.hardened = hardened;
+        self
     }
 
-    /// Probes whether the given expression can be compiled.
+    /// Turns normalization of the environment of probes on or off. It is on
+    /// by default; turn it off to see how a probe behaves in the user's own
+    /// environment when debugging it.
+    ///
+    /// The variables removed are those that enable incremental compilation
+    /// (`CARGO_INCREMENTAL`), compiler debug logging (`RUSTC_LOG`,
+    /// `RUSTC_LOG_COLOR` and `RUST_LOG`), crash reports (`RUSTC_ICE`) and
+    /// the terminal size (`COLUMNS` and `LINES`), which a build may set for
+    /// the crate being built but which only slow down probes or change their
+    /// diagnostics. The locale is set to `C` with `LC_ALL` and `LANG`, so that
+    /// the linker's messages, which some probes inspect, are in English
+    /// everywhere. Variables set with [`env`](#method.env) are passed
+    /// regardless.
     ///
     /// # Examples
     ///
     /// ```
     /// use feature_probe::Probe;
     ///
-    /// let probe = Probe::new();
-    /// assert!(   probe.probe_expression("3 + 4") );
-    /// assert!( ! probe.probe_expression("3 + true") );
-    pub fn probe_expression(&self, expression: &str) -> bool {
-        self.probe(&format!("fn main() {{ {}; }}", expression))
+    /// let mut probe = Probe::new();
+    /// probe.sanitize_env(false);
+    /// assert!( probe.probe_type("u32") );
+    /// ```
+    pub fn sanitize_env(&mut self, sanitize: bool) -> &mut Self {
+        self.sanitize_env = sanitize;
+        self
     }
 
-    /// Probes for whether a whole program can be compiled.
+    /// Adds a variable to the environment allowlist used in
+    /// [`hardened`](#method.hardened) mode.
     ///
-    /// # Panics
+    /// The allowlist starts with the variables needed to find and run
+    /// `rustc` through rustup: `PATH`, `HOME`, `RUSTUP_HOME`,
+    /// `RUSTUP_TOOLCHAIN`, `CARGO_HOME`, temporary-directory and
+    /// library-path variables, and their Windows equivalents.
+    pub fn allow_env<S: Into<OsString>>(&mut self, var: S) -> &mut Self {
+        self.env_allowlist.insert(var.into());
+        self
+    }
+
+    /// Adds an argument to pass to `rustc` for every probe.
     ///
-    /// If the child `rustc` cannot be started or communicated with.
+    /// When `cfg`s are checked, because a `--check-cfg` is passed this way,
+    /// each `--cfg` passed this way is also declared with `--check-cfg`, so
+    /// that probe programs using it don't get `unexpected_cfgs` warnings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOptions};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.arg("--cfg").arg("probing");
+    /// assert!( probe.probe_expression("{ #[cfg(probing)] let x = 1; x }") );
+    ///
+    /// probe.arg("--check-cfg=cfg(unused)").arg("--cfg=mode=\"fast\"");
+    /// assert!( probe.probe_with("fn main() { if cfg!(probing) && cfg!(mode = \"fast\") { } }",
+    ///                           ProbeOptions::new().deny_warnings(true)) );
+    /// ```
+    pub fn arg<S: Into<OsString>>(&mut self, arg: S) -> &mut Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds several arguments to pass to `rustc` for every probe.
+    pub fn args<I>(&mut self, args: I) -> &mut Self
+        where I: IntoIterator,
+              I::Item: Into<OsString>
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets a configuration option for every probe, as with `--cfg`.
+    ///
+    /// The option is either a name, such as `"probing"`, or a name and a
+    /// quoted value, such as `"feature=\"simd\""`; see
+    /// [`cfg_value`](#method.cfg_value) for the latter without the quoting.
+    /// When `cfg`s are checked, it is also declared with `--check-cfg`, as
+    /// described for [`arg`](#method.arg).
     ///
     /// # Examples
     ///
     /// ```
-    /// # extern crate feature_probe;
-    /// # fn main() {
     /// use feature_probe::Probe;
     ///
-    /// let probe = Probe::new();
-    /// assert!(   probe.probe("fn main() { }") );
-    /// assert!( ! probe.probe("fn main(args: Vec<String>) { }") );
-    /// # }
+    /// let mut probe = Probe::new();
+    /// probe.cfg("probing").cfg("feature=\"simd\"");
+    /// assert!( probe.probe_expression("{ #[cfg(all(probing, feature = \"simd\"))] let x = 1; x }") );
     /// ```
-    pub fn probe(&self, code: &str) -> bool {
-        self.probe_result(code).expect("Probe::probe")
+    pub fn cfg<S: AsRef<str>>(&mut self, spec: S) -> &mut Self {
+        self.arg(format!("--cfg={}", spec.as_ref()))
     }
 
-    /// Probes for whether a whole program can be compiled.
+    /// Sets a configuration option with a value for every probe, quoting the
+    /// value, so that `cfg_value("feature", "simd")` passes
+    /// `--cfg=feature="simd"`.
     ///
     /// # Examples
     ///
     /// ```
-    /// # extern crate feature_probe;
-    /// # fn main() {
     /// use feature_probe::Probe;
     ///
-    /// let probe = Probe::new();
-    /// assert_eq!( probe.probe_result("fn main() { }").unwrap(),                  true );
-    /// assert_eq!( probe.probe_result("fn main(args: Vec<String>) { }").unwrap(), false );
-    /// # }
+    /// let mut probe = Probe::new();
+    /// probe.cfg_value("mode", "a \"quoted\" value");
+    /// assert!( probe.probe_expression("{ #[cfg(mode = \"a \\\"quoted\\\" value\")] let x = 1; x }") );
     /// ```
-    pub fn probe_result(&self, code: &str) -> io::Result<bool> {
-        let mut child = Command::new(&self.rustc)
-            .arg("--out-dir")
-            .arg(&self.out_dir)
-            .arg("--emit=obj")
-            .arg("-")
-            .stdin(Stdio::piped())
-            .spawn()?;
+    pub fn cfg_value<N, V>(&mut self, name: N, value: V) -> &mut Self
+        where N: AsRef<str>,
+              V: AsRef<str>
+    {
+        self.cfg(format!("{}={}", name.as_ref(), cfg_string(value.as_ref())))
+    }
 
-        child
-            .stdin
-            .as_mut().unwrap()
-            .write_all(code.as_bytes())?;
+    /// Declares the expected configuration options for every probe, as with
+    /// `--check-cfg`, such as `"cfg(feature, values(\"simd\"))"`.
+    ///
+    /// This turns on checking `cfg`s, so that a probe program using an
+    /// undeclared one gets an `unexpected_cfgs` warning. Those set with
+    /// [`cfg`](#method.cfg) are declared automatically. Compilers before
+    /// Rust 1.80 reject `--check-cfg`.
+    pub fn check_cfg<S: AsRef<str>>(&mut self, spec: S) -> &mut Self {
+        self.arg(format!("--check-cfg={}", spec.as_ref()))
+    }
 
-        Ok(child.wait()?.success())
+    /// Sets an environment variable for every probe's `rustc`.
+    ///
+    /// In [`hardened`](#method.hardened) mode, variables set this way are
+    /// passed even if they aren't on the allowlist.
+    pub fn env<K, V>(&mut self, key: K, value: V) -> &mut Self
+        where K: Into<OsString>,
+              V: Into<OsString>
+    {
+        self.envs.insert(key.into(), value.into());
+        self
     }
-}
 
-impl Default for Probe {
-    fn default() -> Self {
-        Probe::new()
+    /// Computes a fingerprint of this probe's configuration.
+    ///
+    /// The fingerprint is a hash of everything that can affect probe
+    /// answers: the identity of the compiler (its `rustc -vV` output), the
+    /// target, edition, arguments, environment variables, and hardened-mode
+    /// settings. Two identically configured probes using the same compiler
+    /// have the same fingerprint, regardless of the order in which
+    /// environment settings were made, and the fingerprint is stable across
+    /// platforms and Rust versions, so it can key caches and snapshots.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc -vV` cannot be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut a = Probe::new();
+    /// a.env("A", "1").env("B", "2");
+    /// let mut b = Probe::new();
+    /// b.env("B", "2").env("A", "1");
+    /// assert_eq!( a.fingerprint().unwrap(), b.fingerprint().unwrap() );
+    ///
+    /// b.edition("2018");
+    /// assert_ne!( a.fingerprint().unwrap(), b.fingerprint().unwrap() );
+    /// ```
+    pub fn fingerprint(&self) -> io::Result<u64> {
+        let mut fp = fingerprint::Fingerprinter::new();
+        fp.str(&self.rustc_verbose_version()?)
+          .str(&format!("{:?}", self.profile()))
+          .str(&self.emit)
+          .option(self.target.as_ref(), |fp, target| { fp.os_str(target); })
+          .option(self.edition.as_ref(), |fp, edition| { fp.str(edition); })
+          .option(self.crate_type, |fp, crate_type| { fp.str(crate_type); });
+
+        fp.bytes(&compat::le_bytes(self.wrapper.len() as u64));
+        for word in &self.wrapper {
+            fp.os_str(word);
+        }
+
+        fp.bytes(&compat::le_bytes(self.args.len() as u64));
+        for arg in &self.args {
+            fp.os_str(arg);
+        }
+
+        fp.bytes(&compat::le_bytes(self.envs.len() as u64));
+        for (key, value) in &self.envs {
+            fp.os_str(key).os_str(value);
+        }
+
+        fp.option(if self.hardened { Some(&self.env_allowlist) } else { None }, |fp, allowlist| {
+            for var in allowlist {
+                fp.os_str(var);
+            }
+        });
+
+        fp.option(self.runner.as_ref(), |fp, runner| {
+            for word in runner {
+                fp.os_str(word);
+            }
+        });
+
+        Ok(fp.finish())
+    }
+
+    /// Sets how long a probe's `rustc` may run before it is killed.
+    ///
+    /// A probe that times out has an [`Unknown`](enum.ProbeOutcome.html)
+    /// outcome, and [`probe_result`](#method.probe_result) returns an error
+    /// of kind `TimedOut`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.timeout(Duration::from_secs(60));
+    /// assert!( probe.probe_type("u32") );
+    /// ```
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the CPU time a probe's `rustc` may use, rounded up to whole
+    /// seconds on Unix.
+    ///
+    /// The limit is enforced with `setrlimit` on Linux and macOS and with a
+    /// job object on Windows; elsewhere, probing fails with an error. A
+    /// `rustc` killed for exceeding the limit gives an
+    /// [`Unknown`](enum.ProbeOutcome.html) outcome rather than a negative
+    /// one.
+    ///
+    /// Needs the `resource-limits` feature, and so Rust 1.34.
+    #[cfg(feature = "resource-limits")]
+    pub fn cpu_time_limit(&mut self, limit: Duration) -> &mut Self {
+        self.limits.cpu_time = Some(limit);
+        self
+    }
+
+    /// Limits the memory, in bytes, that a probe's `rustc` may use.
+    ///
+    /// This is enforced like [`cpu_time_limit`](#method.cpu_time_limit), and
+    /// like it, a `rustc` that runs out of memory gives an
+    /// [`Unknown`](enum.ProbeOutcome.html) outcome.
+    ///
+    /// Needs the `resource-limits` feature, and so Rust 1.34.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOutcome};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.memory_limit(4 << 30);
+    /// if cfg!(any(target_os = "linux", target_os = "macos", windows)) {
+    ///     assert_eq!( probe.probe_outcome("fn main() { }"), ProbeOutcome::Present );
+    /// }
+    /// ```
+    #[cfg(feature = "resource-limits")]
+    pub fn memory_limit(&mut self, bytes: u64) -> &mut Self {
+        self.limits.memory = Some(bytes);
+        self
+    }
+
+    /// Runs probes' `rustc` at a lower priority, so that heavy probing
+    /// doesn't starve the rest of a parallel build.
+    ///
+    /// On Unix, this raises the process's nice value by 10, and on Linux it
+    /// also lowers the I/O priority as `ionice` does. On Windows, it uses the
+    /// below-normal priority class. Elsewhere, or if the system refuses, the
+    /// priority is left alone.
+    ///
+    /// Needs the `resource-limits` feature, and so Rust 1.34.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.low_priority(true);
+    /// assert!( probe.probe_type("u32") );
+    /// ```
+    #[cfg(feature = "resource-limits")]
+    pub fn low_priority(&mut self, low: bool) -> &mut Self {
+        self.limits.low_priority = low;
+        self
     }
+
+    /// Determines the version of `rustc` in use.
+    ///
+    /// The `rustc -vV` output this is parsed from is cached, and shared by
+    /// clones of this probe.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc -vV` cannot be run, or its output has no `release:` line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, Version};
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.rustc_version().unwrap() >= Version::new(1, 34, 0) );
+    /// ```
+    pub fn rustc_version(&self) -> io::Result<Version> {
+        let verbose = self.rustc_verbose_version()?;
+        version::verbose_field(&verbose, "release")
+            .and_then(Version::parse)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+                                          "no release in `rustc -vV` output"))
+    }
+
+    /// Determines the host triple of `rustc` in use, the target it compiles
+    /// for when none is set.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc -vV` cannot be run, or its output has no `host:` line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( ! probe.host().unwrap().is_empty() );
+    /// ```
+    pub fn host(&self) -> io::Result<String> {
+        let verbose = self.rustc_verbose_version()?;
+        version::verbose_field(&verbose, "host")
+            .map(str::to_owned)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+                                          "no host in `rustc -vV` output"))
+    }
+
+    /// Whether probes are compiled for a target other than the host.
+    ///
+    /// Probes that run what they compile, or that need the target's standard
+    /// library, can only work when this is false or the target is installed.
+    ///
+    /// # Errors
+    ///
+    /// If the host cannot be determined; see [`host`](#method.host).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// assert!( ! probe.is_cross_compiling().unwrap() );
+    ///
+    /// let host = probe.host().unwrap();
+    /// probe.target(host);
+    /// assert!( ! probe.is_cross_compiling().unwrap() );
+    ///
+    /// probe.target("thumbv7em-none-eabihf");
+    /// assert!(   probe.is_cross_compiling().unwrap() );
+    /// ```
+    pub fn is_cross_compiling(&self) -> io::Result<bool> {
+        match self.target {
+            Some(ref target) => Ok(*target != *self.host()?),
+            None             => Ok(false),
+        }
+    }
+
+    fn rustc_verbose_version(&self) -> io::Result<String> {
+        let key: Vec<OsString> = self.wrapper.iter().chain(Some(&self.rustc)).cloned().collect();
+
+        let mut cache = self.rustc_info.lock().unwrap();
+        if let Some((ref rustc, ref verbose)) = *cache {
+            if *rustc == key {
+                self.stats.lock().unwrap().cache_hits += 1;
+                return Ok(verbose.clone());
+            }
+        }
+
+        let verbose = self.run_rustc(&["-vV"])?;
+        *cache = Some((key, verbose.clone()));
+        Ok(verbose)
+    }
+
+    /// Runs the compiler with the given arguments, returning its output and
+    /// explaining failures in terms of what to fix.
+    fn run_rustc<S: AsRef<OsStr>>(&self, args: &[S]) -> io::Result<String> {
+        if self.hardened {
+            self.check_hardened_rustc()?;
+        }
+
+        let rustc = self.rustc.to_string_lossy();
+
+        let output = self.rustc_command().args(args).output().map_err(|e| {
+            let hint = match e.kind() {
+                io::ErrorKind::NotFound =>
+                    "it was not found; install Rust or set `RUSTC` to the compiler's path",
+                io::ErrorKind::PermissionDenied if is_sandbox_denial(&e) =>
+                    "probably a sandbox forbids starting processes",
+                io::ErrorKind::PermissionDenied =>
+                    "check that it is executable",
+                _ => "",
+            };
+            io::Error::new(e.kind(), format!("cannot run compiler `{}`: {}{}{}",
+                                             rustc, e,
+                                             if hint.is_empty() { "" } else { "; " }, hint))
+        })?;
+
+        if output.status.code().is_none() {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied,
+                                      format!("compiler `{}` was killed ({}), possibly by a \
+                                               sandbox such as seccomp",
+                                              rustc, output.status)));
+        }
+
+        if !output.status.success() {
+            let stderr = text::decode(&output.stderr);
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      format!("`{} {}` failed: {}: {}",
+                                              rustc,
+                                              args.iter()
+                                                  .map(|arg| arg.as_ref().to_string_lossy())
+                                                  .collect::<Vec<_>>()
+                                                  .join(" "),
+                                              output.status,
+                                              stderr.lines().next().unwrap_or("").trim())));
+        }
+
+        Ok(text::decode(&output.stdout))
+    }
+
+    /// Checks that the compiler can be run, so that a misconfiguration is
+    /// reported up front with a clear message instead of failing the first
+    /// probe.
+    ///
+    /// # Errors
+    ///
+    /// With kind `NotFound` if the compiler doesn't exist, and
+    /// `PermissionDenied` if it isn't executable or a sandbox prevents
+    /// running it; the message says which. In
+    /// [`hardened`](#method.hardened) mode, also if the compiler isn't
+    /// `rustc` or there is a wrapper command.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use feature_probe::Probe;
+    ///
+    /// assert!( Probe::new().check().is_ok() );
+    ///
+    /// std::env::set_var("RUSTC", "/no/such/rustc");
+    /// let probe = Probe::new();
+    /// std::env::remove_var("RUSTC");
+    /// assert_eq!( probe.check().unwrap_err().kind(), io::ErrorKind::NotFound );
+    /// ```
+    pub fn check(&self) -> io::Result<()> {
+        if self.hardened {
+            self.check_hardened_rustc()?;
+        }
+
+        if self.profile().accepts_rustc_flags() {
+            self.rustc_verbose_version().map(|_| ())
+        } else {
+            self.run_rustc(&["--version"]).map(|_| ())
+        }
+    }
+
+    /// Whether the compiler accepts flags introduced in the given release,
+    /// assuming it does if the version can't be determined.
+    fn rustc_supports(&self, since: Version) -> bool {
+        self.rustc_version().map(|version| version >= since).unwrap_or(true)
+    }
+
+    /// Whether the compiler accepts unstable features, as nightly and
+    /// development builds do, and others do with `RUSTC_BOOTSTRAP=1`.
+    fn accepts_unstable(&self) -> io::Result<bool> {
+        let verbose = self.rustc_verbose_version()?;
+        let release = version::verbose_field(&verbose, "release").unwrap_or("");
+        Ok(release.contains("-nightly") || release.contains("-dev")
+           || env::var_os("RUSTC_BOOTSTRAP").map_or(false, |value| value.to_str() == Some("1")))
+    }
+
+    /// Sets the backend that decides whether probe programs compile.
+    ///
+    /// The default is [`RustcBackend`](struct.RustcBackend.html). See
+    /// [`ProbeBackend`](trait.ProbeBackend.html) for why you might change it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ScriptedBackend};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.backend(ScriptedBackend::new().default_answer(true));
+    /// assert!( probe.probe_type("u512") );
+    /// ```
+    pub fn backend<B: ProbeBackend + 'static>(&mut self, backend: B) -> &mut Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+
+    /// Sets where the `cargo:` directives emitted through this probe go.
+    ///
+    /// The default is [`StdoutReporter`](struct.StdoutReporter.html). See
+    /// [`Reporter`](trait.Reporter.html) for why you might change it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, RecordingReporter};
+    ///
+    /// let recorder = RecordingReporter::new();
+    /// let mut probe = Probe::new();
+    /// probe.reporter(recorder.clone());
+    ///
+    /// probe.emit_all_syntax_cfgs();
+    /// assert!( recorder.cfgs().contains(&"raw_identifiers".to_owned()) );
+    /// ```
+    pub fn reporter<R: Reporter + 'static>(&mut self, reporter: R) -> &mut Self {
+        self.reporter = Arc::new(reporter);
+        self
+    }
+
+    /// Sets the context of the current build, overriding
+    /// [`BuildContext::detect`](enum.BuildContext.html#method.detect).
+    pub fn build_context(&mut self, context: BuildContext) -> &mut Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// The context of the current build, as set with
+    /// [`build_context`](#method.build_context) or else detected.
+    pub fn context(&self) -> BuildContext {
+        self.context.unwrap_or_else(BuildContext::detect)
+    }
+
+    /// Answers every probe with `present`, without compiling, in builds of
+    /// the given context.
+    ///
+    /// Use this to keep IDE builds fast, with answers that make the code
+    /// analyze well, or to give Miri builds answers for what Miri supports,
+    /// while real builds probe fully. Probes that run or
+    /// inspect what they compile can't be answered this way and fail
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{BuildContext, Probe};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.answer_in(BuildContext::Ide, true)
+    ///      .build_context(BuildContext::Ide);
+    /// assert!( probe.probe_type("u512") );
+    ///
+    /// probe.build_context(BuildContext::Build);
+    /// assert!( ! probe.probe_type("u512") );
+    /// ```
+    pub fn answer_in(&mut self, context: BuildContext, present: bool) -> &mut Self {
+        self.answers.retain(|answer| answer.0 != context);
+        self.answers.push((context, present));
+        self
+    }
+
+    /// Chooses what to answer when a probe can't be carried out.
+    ///
+    /// The policy applies wherever a program is compiled, so it covers every
+    /// probing method. Probes that run or inspect what they compile can't
+    /// use an assumed answer and fail anyway.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, UnavailablePolicy};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.target("no-such-target")
+    ///      .on_probe_unavailable(UnavailablePolicy::AssumeAbsent);
+    /// assert!( ! probe.probe_type("u32") );
+    /// ```
+    pub fn on_probe_unavailable(&mut self, policy: UnavailablePolicy) -> &mut Self {
+        self.unavailable = policy;
+        self
+    }
+
+    /// Sets a file in which to record the answer of every probe that is
+    /// carried out, for [`UnavailablePolicy::FromCache`](enum.UnavailablePolicy.html)
+    /// to fall back on.
+    ///
+    /// Answers are keyed by the probe program and the settings that affect
+    /// compiling it, but not by the compiler, so that they are still found
+    /// when the compiler can't be run.
+    ///
+    /// When the first probe is carried out, this emits
+    /// `cargo:rerun-if-changed` for the file and `cargo:rerun-if-env-changed`
+    /// for the variables that choose the compiler and its flags (`RUSTC`,
+    /// `RUSTC_WRAPPER`, `RUSTC_WORKSPACE_WRAPPER`, `RUSTUP_TOOLCHAIN` and
+    /// `CARGO_ENCODED_RUSTFLAGS`), so that the build script probes afresh
+    /// after a toolchain change. Like any `rerun-if` directive, these stop
+    /// Cargo from rerunning the build script whenever any file in the
+    /// package changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, UnavailablePolicy};
+    ///
+    /// let cache = std::env::temp_dir().join(format!("fp-doc-cache-{}", std::process::id()));
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.cache_file(&cache);
+    /// assert!( probe.probe_type("u32") );
+    ///
+    /// probe.timeout(std::time::Duration::from_millis(0))
+    ///      .on_probe_unavailable(UnavailablePolicy::FromCache);
+    /// assert!( probe.probe_type("u32") );
+    ///
+    /// std::fs::remove_file(&cache).unwrap();
+    /// ```
+    pub fn cache_file<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.cache_file = Some(path.into());
+        self.cache_rerun = Arc::new(AtomicBool::new(false));
+        self
+    }
+
+    /// Keeps the cache described for [`cache_file`](#method.cache_file) in a
+    /// file named `feature-probe.cache` in the given directory, such as
+    /// `OUT_DIR`.
+    pub fn cache_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.cache_file(dir.as_ref().join(CACHE_FILE_NAME))
+    }
+
+    /// Forgets every cached answer by removing the
+    /// [`cache_file`](#method.cache_file), if there is one.
+    ///
+    /// # Errors
+    ///
+    /// If the file exists but cannot be removed.
+    pub fn clear_cache(&self) -> io::Result<()> {
+        match self.cache_file {
+            Some(ref path) => match fs::remove_file(path) {
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                result                                             => result,
+            },
+            None           => Ok(()),
+        }
+    }
+
+    /// Summarizes the answers in the [`cache_file`](#method.cache_file),
+    /// which has none if there is no cache file.
+    ///
+    /// # Errors
+    ///
+    /// If the file exists but cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let dir = std::env::temp_dir().join(format!("fp-doc-cache-dir-{}", std::process::id()));
+    /// std::fs::create_dir_all(&dir).unwrap();
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.cache_dir(&dir);
+    /// probe.probe_type("u32");
+    /// probe.probe_type("u512");
+    ///
+    /// let stats = probe.cache_stats().unwrap();
+    /// assert_eq!( (stats.entries, stats.present, stats.absent), (2, 1, 1) );
+    ///
+    /// probe.clear_cache().unwrap();
+    /// assert_eq!( probe.cache_stats().unwrap().entries, 0 );
+    /// std::fs::remove_dir(&dir).unwrap();
+    /// ```
+    pub fn cache_stats(&self) -> io::Result<CacheStats> {
+        let answers = match self.cache_file {
+            Some(ref path) => cache::load(path)?,
+            None           => BTreeMap::new(),
+        };
+
+        let present = answers.values().filter(|present| **present).count();
+        Ok(CacheStats {
+            entries: answers.len(),
+            present: present,
+            absent:  answers.len() - present,
+        })
+    }
+
+    /// Turns sharing of answers across the process on or off.
+    ///
+    /// Probes that share answers remember each answer in a map global to the
+    /// process, keyed by the probe's [`fingerprint`](#method.fingerprint)
+    /// and program, and answer from it when they can instead of compiling.
+    /// This saves compiling the same program repeatedly when helper
+    /// functions each create their own probes. The map doesn't distinguish
+    /// [`backend`](#method.backend)s, so don't share answers between probes
+    /// with different backends.
+    ///
+    /// Probes that need more from a compilation than its answer always
+    /// compile, and neither consult nor add to the map: those that run the
+    /// program, such as [`probe_run`](#method.probe_run), those that read
+    /// the compiler's output, such as [`emitted`](#method.emitted), and
+    /// [`probe_compile_time`](#method.probe_compile_time).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut a = Probe::new();
+    /// a.share_answers(true);
+    /// let mut b = Probe::new();
+    /// b.share_answers(true);
+    ///
+    /// assert!( a.probe_type("i128") );
+    /// assert!( b.probe_type("i128") );
+    /// assert_eq!( b.stats().compilations, 0 );
+    ///
+    /// assert!( b.probe_run("fn main() { }").unwrap().success() );
+    /// assert!( b.probe_run("fn main() { }").unwrap().success() );
+    /// assert_eq!( b.stats().compilations, 2 );
+    /// ```
+    pub fn share_answers(&mut self, share: bool) -> &mut Self {
+        self.share_answers = share;
+        self
+    }
+
+    /// Sets a function to adjust each probe's `rustc` command just before it
+    /// is handed to the [`backend`](#method.backend) to run.
+    ///
+    /// This is for adjustments too specialized for an option of their own,
+    /// such as placing the compiler in a process group or setting variables
+    /// a particular build system needs. The hook runs after the command is
+    /// otherwise complete, so it can also override anything set by the
+    /// probe. Changes it makes are not reflected in
+    /// [`fingerprint`](#method.fingerprint). Clones of the probe share the
+    /// hook.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.before_spawn(|command| { command.arg("--cfg").arg("probing"); });
+    /// assert!( probe.probe_expression("{ #[cfg(probing)] let x = 1; x }") );
+    /// ```
+    pub fn before_spawn<F>(&mut self, hook: F) -> &mut Self
+        where F: FnMut(&mut Command) + Send + 'static
+    {
+        self.before_spawn = Some(Arc::new(Mutex::new(hook)));
+        self
+    }
+
+    /// Sets a function to call after every probe, with a
+    /// [`ProbeEvent`](struct.ProbeEvent.html) describing it, such as for
+    /// feeding build analytics. Clones of the probe share the observer.
+    ///
+    /// The observer is called even for probes answered without compiling,
+    /// and for probes that could not be carried out.
+    pub fn observer<F>(&mut self, observer: F) -> &mut Self
+        where F: Fn(&ProbeEvent) + Send + Sync + 'static
+    {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Sets a token for abandoning this probe and its clones from another
+    /// thread, killing a running compiler. See
+    /// [`CancellationToken`](struct.CancellationToken.html).
+    pub fn cancellation(&mut self, token: CancellationToken) -> &mut Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().map_or(false, CancellationToken::is_cancelled)
+    }
+
+    /// Reports each probe as it starts and finishes, with a running count
+    /// and the elapsed time, so that a build script running many probes
+    /// isn't silent and a probe that hangs can be identified. Clones of the
+    /// probe share the count. Name probes with [`named`](#method.named) to
+    /// tell them apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, Progress, RecordingReporter};
+    ///
+    /// let recorder = RecordingReporter::new();
+    /// let mut probe = Probe::new();
+    /// probe.reporter(recorder.clone()).progress(Progress::CargoWarning);
+    ///
+    /// probe.named("u512").probe_type("u512");
+    /// let directives = recorder.directives();
+    /// assert!( directives[0].starts_with("cargo:warning=feature-probe: [1] u512: absent in ") );
+    /// ```
+    pub fn progress(&mut self, output: Progress) -> &mut Self {
+        self.progress = match output {
+            Progress::Off => None,
+            output        => Some(Arc::new(progress::Tracker::new(output))),
+        };
+        self
+    }
+
+    /// Returns the counters and timings accumulated so far by this probe and
+    /// its clones.
+    pub fn stats(&self) -> ProbeStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Resets the statistics returned by [`stats`](#method.stats) to zero.
+    pub fn reset_stats(&self) {
+        *self.stats.lock().unwrap() = ProbeStats::default();
+    }
+
+    /// Emits `cargo:rustc-cfg=NAME` through this probe's
+    /// [`reporter`](#method.reporter), with the name prefixed by any
+    /// [`cfg_prefix`](#method.cfg_prefix).
+    pub fn emit_cfg(&self, name: &str) {
+        self.reporter.cfg(&format!("{}{}", self.cfg_prefix, name));
+    }
+
+    /// Emits `cargo:rustc-cfg=NAME="VALUE"` through this probe's
+    /// [`reporter`](#method.reporter), with the name prefixed by any
+    /// [`cfg_prefix`](#method.cfg_prefix) and the value escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, RecordingReporter};
+    ///
+    /// let recorder = RecordingReporter::new();
+    /// let mut probe = Probe::new();
+    /// probe.reporter(recorder.clone());
+    ///
+    /// probe.emit_cfg_value("api_level", "3");
+    /// probe.emit_check_cfg("api_level", &["1", "2", "3"]);
+    /// assert_eq!( recorder.directives(),
+    ///             vec!["cargo:rustc-cfg=api_level=\"3\"",
+    ///                  "cargo:rustc-check-cfg=cfg(api_level, values(\"1\", \"2\", \"3\"))"] );
+    /// ```
+    pub fn emit_cfg_value(&self, name: &str, value: &str) {
+        self.reporter.cfg_value(&format!("{}{}", self.cfg_prefix, name), value);
+    }
+
+    /// Emits `cargo:rustc-check-cfg` through this probe's
+    /// [`reporter`](#method.reporter), declaring the given values for the
+    /// configuration option, with the name prefixed by any
+    /// [`cfg_prefix`](#method.cfg_prefix). See
+    /// [`Reporter::check_cfg`](trait.Reporter.html#method.check_cfg).
+    pub fn emit_check_cfg(&self, name: &str, values: &[&str]) {
+        self.reporter.check_cfg(&format!("{}{}", self.cfg_prefix, name), values);
+    }
+
+    /// Emits `cargo:KEY=VALUE` metadata through this probe's
+    /// [`reporter`](#method.reporter).
+    ///
+    /// # Panics
+    ///
+    /// If the key contains `=` or `:`, either argument contains a newline,
+    /// or the key is reserved by Cargo. See
+    /// [`Reporter::metadata`](trait.Reporter.html#method.metadata).
+    pub fn emit_metadata(&self, key: &str, value: &str) {
+        self.reporter.metadata(key, value);
+    }
+
+    /// Emits a warning through this probe's [`reporter`](#method.reporter),
+    /// which Cargo shows once the build script is done. See
+    /// [`Reporter::warning`](trait.Reporter.html#method.warning).
+    ///
+    /// The probe reports its own problems this way too, such as a probe
+    /// that couldn't be carried out being answered by the
+    /// [`on_probe_unavailable`](#method.on_probe_unavailable) policy.
+    pub fn emit_warning(&self, message: &str) {
+        self.reporter.warning(message);
+    }
+
+    /// Emits an error through this probe's [`reporter`](#method.reporter),
+    /// as `cargo::error` where Cargo understands it and otherwise as a
+    /// warning. See [`Reporter::error`](trait.Reporter.html#method.error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, RecordingReporter};
+    ///
+    /// let recorder = RecordingReporter::new();
+    /// let mut probe = Probe::new();
+    /// probe.reporter(recorder.clone());
+    ///
+    /// probe.emit_warning("no SIMD; using the portable fallback");
+    /// probe.emit_error("no usable allocator\ntry enabling the `alloc` feature");
+    /// assert_eq!( recorder.directives(),
+    ///             vec!["cargo:warning=no SIMD; using the portable fallback",
+    ///                  "cargo:warning=error: no usable allocator",
+    ///                  "cargo:warning=error: try enabling the `alloc` feature"] );
+    /// ```
+    pub fn emit_error(&self, message: &str) {
+        self.reporter.error(message);
+    }
+
+    /// Emits `cargo:rustc-cfg=NAME` for every successful probe in the report,
+    /// through this probe's [`reporter`](#method.reporter).
+    pub fn emit_report_cfgs(&self, report: &ProbeReport) {
+        for (name, present) in report {
+            if present {
+                self.emit_cfg(name);
+            }
+        }
+    }
+
+    /// Exports every result in the report as `links` metadata, as
+    /// [`ProbeReport::emit_metadata`](struct.ProbeReport.html#method.emit_metadata)
+    /// does, through this probe's [`reporter`](#method.reporter).
+    pub fn emit_report_metadata(&self, report: &ProbeReport) {
+        report.emit_metadata_to(&*self.reporter);
+    }
+
+    /// Probes for the existence of the given type by name.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_type("u32") );
+    /// assert!( ! probe.probe_type("u512") );
+    /// ```
+    pub fn probe_type(&self, type_name: &str) -> bool {
+        self.probe_type_with(type_name, &ProbeOptions::new())
+    }
+
+    /// Probes whether the given expression can be compiled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_expression("3 + 4") );
+    /// assert!( ! probe.probe_expression("3 + true") );
+    pub fn probe_expression(&self, expression: &str) -> bool {
+        self.probe(&format!("fn main() {{ {}; }}", expression))
+    }
+
+    /// Probes for whether a whole program can be compiled.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate feature_probe;
+    /// # fn main() {
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe("fn main() { }") );
+    /// assert!( ! probe.probe("fn main(args: Vec<String>) { }") );
+    /// # }
+    /// ```
+    pub fn probe(&self, code: &str) -> bool {
+        self.probe_result(code).expect("Probe::probe")
+    }
+
+    /// Probes for whether a whole program can be compiled, given as anything
+    /// that can be displayed, such as `format_args!`. The program is only
+    /// rendered if it is needed to answer the probe.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_display(format_args!("fn main() {{ let _: {} = 0; }}", "i128")) );
+    /// ```
+    pub fn probe_display<C: fmt::Display>(&self, code: C) -> bool {
+        self.probe_from(|| code.to_string())
+    }
+
+    /// Probes for whether the program returned by the given function can be
+    /// compiled. The function is only called if the program is needed to
+    /// answer the probe, which it isn't when an answer was given with
+    /// [`answer_in`](#method.answer_in).
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{BuildContext, Probe};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.build_context(BuildContext::Ide).answer_in(BuildContext::Ide, true);
+    /// assert!( probe.probe_from(|| unreachable!()) );
+    /// ```
+    pub fn probe_from<F: FnOnce() -> String>(&self, code: F) -> bool {
+        self.probe_result_from(code).expect("Probe::probe_from")
+    }
+
+    /// Probes for whether the program returned by the given function can be
+    /// compiled, calling it only if the program is needed.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_result_from<F: FnOnce() -> String>(&self, code: F) -> io::Result<bool> {
+        // An observer is told the program, so it has to be rendered anyway.
+        match self.preset_answer() {
+            Some(present) if self.observer.is_none() => Ok(present),
+            _                                        => self.probe_result(&code()),
+        }
+    }
+
+    /// Probes for whether a program compiles as a test crate, with `--test`,
+    /// so that crates generating test code can check what the test harness
+    /// on this toolchain accepts, such as `#[bench]` or a custom test
+    /// framework.
+    ///
+    /// The harness provides `main`, so the program doesn't need one.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_in_test_harness("#[test] fn works() { }") );
+    /// assert!( ! probe.probe_in_test_harness("#[test] fn works(_: u32) { }") );
+    /// assert!( ! probe.probe("#[test] fn works() { }") );
+    /// ```
+    pub fn probe_in_test_harness(&self, code: &str) -> bool {
+        let mut probe = self.clone();
+        probe.crate_type = None;
+        probe.arg("--test");
+        probe.probe(code)
+    }
+
+    /// Probes for whether a whole program can be compiled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate feature_probe;
+    /// # fn main() {
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert_eq!( probe.probe_result("fn main() { }").unwrap(),                  true );
+    /// assert_eq!( probe.probe_result("fn main(args: Vec<String>) { }").unwrap(), false );
+    /// # }
+    /// ```
+    pub fn probe_result(&self, code: &str) -> io::Result<bool> {
+        if self.hardened {
+            self.probe_result_in_temp(code)
+        } else {
+            self.compile_in(None, code)
+        }
+    }
+
+    /// Compiles with output going to a fresh temporary directory, for probes
+    /// whose output must not land in, or be read back from, the shared
+    /// output directory.
+    fn probe_result_in_temp(&self, code: &str) -> io::Result<bool> {
+        let dir = temp::TempDir::new()?;
+        self.compile_in(Some(dir.path()), code)
+    }
+
+    /// A copy of the probe that always runs the compiler, ignoring preset
+    /// and cached answers, for probes that need more from a compilation than
+    /// its answer.
+    fn compiling_afresh(&self) -> Probe {
+        let mut probe = self.clone();
+        probe.answers.clear();
+        probe.unavailable = UnavailablePolicy::Panic;
+        probe.cache_file = None;
+        probe.share_answers = false;
+        probe
+    }
+
+    /// Probes for whether a whole program can be compiled, distinguishing
+    /// programs that don't compile from probes that couldn't be carried out.
+    ///
+    /// The outcome is [`Unknown`](enum.ProbeOutcome.html) when
+    /// [`probe_result`](#method.probe_result) would return an error, such as
+    /// when `rustc` cannot be started, runs past the
+    /// [`timeout`](#method.timeout), or is killed for exceeding a resource
+    /// limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOutcome};
+    ///
+    /// let probe = Probe::new();
+    /// assert_eq!( probe.probe_outcome("fn main() { }"),                  ProbeOutcome::Present );
+    /// assert_eq!( probe.probe_outcome("fn main(args: Vec<String>) { }"), ProbeOutcome::Absent );
+    /// ```
+    pub fn probe_outcome(&self, code: &str) -> ProbeOutcome {
+        outcome::from_result(&self.probe_result(code))
+    }
+
+    /// Compiles the program with output going to the given directory, or to
+    /// a fresh subdirectory of the output directory if none is given.
+    ///
+    /// Compilers that can't read programs from standard input get them in a
+    /// file in that directory.
+    fn compile_in(&self, dir: Option<&Path>, code: &str) -> io::Result<bool> {
+        // Each probe gets a directory of its own, removed even if probing
+        // panics, so that concurrent probes can't clobber each other's
+        // artifacts.
+        let temp;
+        let dir = match dir {
+            Some(dir) => dir,
+            None      => {
+                temp = temp::TempDir::new_in(Path::new(&self.out_dir))?;
+                temp.path()
+            }
+        };
+
+        if !self.profile().reads_stdin() {
+            compat::write(dir.join(INPUT_FILE), code)?;
+        }
+        self.compile(self.command_in(Some(dir)), code)
+    }
+
+    fn compile(&self, command: Command, code: &str) -> io::Result<bool> {
+        let name   = self.label.as_ref().map_or("(unnamed)", String::as_str);
+        let number = self.progress.as_ref().map(|tracker| tracker.start(name));
+        let start  = Instant::now();
+        let (result, cached) = self.answer(command, code);
+
+        if let (Some(tracker), Some(number)) = (self.progress.as_ref(), number) {
+            let answer = match result {
+                Ok(true)   => String::from("present"),
+                Ok(false)  => String::from("absent"),
+                Err(ref e) => format!("unknown ({})", e),
+            };
+            let answer = if cached { answer + " (cached)" } else { answer };
+            tracker.finish(&*self.reporter, number, name, &answer, start.elapsed());
+        }
+
+        if let Some(ref observer) = self.observer {
+            (**observer)(&event::new(self.label.as_ref().map(String::as_str), code,
+                                     self.cache_key(code), start.elapsed(), cached,
+                                     outcome::from_result(&result)));
+        }
+
+        result
+    }
+
+    /// Answers a probe, along with whether the answer came from somewhere
+    /// other than the compiler.
+    fn answer(&self, command: Command, code: &str) -> (io::Result<bool>, bool) {
+        if self.hardened {
+            if let Err(e) = self.check_hardened_rustc() {
+                return (Err(e), false);
+            }
+        }
+        if let Some(present) = self.preset_answer() {
+            return (Ok(present), true);
+        }
+
+        let fingerprint = if self.share_answers { self.fingerprint().ok() } else { None };
+        if let Some(fingerprint) = fingerprint {
+            if let Some(present) = shared::get(shared::Namespace::Probe, fingerprint, code) {
+                self.stats.lock().unwrap().cache_hits += 1;
+                return (Ok(present), true);
+            }
+        }
+
+        if let Some(ref path) = self.cache_file {
+            if !self.cache_rerun.swap(true, Ordering::SeqCst) {
+                let reporter = &self.reporter;
+                reporter.rerun_if_changed(path);
+                for var in CACHE_ENV_INPUTS {
+                    reporter.rerun_if_env_changed(var);
+                }
+                match cache::malformed_lines(path) {
+                    Ok(0)         => (),
+                    Ok(malformed) =>
+                        reporter.warning(&format!("feature-probe: ignoring {} malformed lines \
+                                                   in cache file {}",
+                                                  malformed, path.display())),
+                    Err(e)        =>
+                        reporter.warning(&format!("feature-probe: cannot read cache file {}: {}",
+                                                  path.display(), e)),
+                }
+            }
+        }
+
+        if let Some(Err(e)) = self.cancel.as_ref().map(cancel::check) {
+            return (Err(e), false);
+        }
+        if self.auto_check {
+            if let Err(e) = self_check::guard(self) {
+                return (Err(e), false);
+            }
+        }
+        if let Err(e) = toolchain::guard(self) {
+            return (Err(e), false);
+        }
+
+        let mut command = command;
+        if let Some(ref hook) = self.before_spawn {
+            let mut hook = hook.lock().unwrap();
+            let hook = &mut *hook;
+            hook(&mut command);
+        }
+
+        let mut compilation = self.compilation(command, code);
+
+        let start  = Instant::now();
+        let result = self.backend.compile(&mut compilation)
+            .and_then(|present| self.check_diagnostics(present, compilation.diagnostics()));
+        stats::record_compilation(&mut self.stats.lock().unwrap(), start.elapsed(), result.is_err());
+
+        match result {
+            Ok(present) => {
+                if let Some(fingerprint) = fingerprint {
+                    shared::insert(shared::Namespace::Probe, fingerprint, code, present);
+                }
+                if let Some(ref path) = self.cache_file {
+                    // The cache is only a fallback, so failing to update it
+                    // shouldn't fail the probe.
+                    if let Err(e) = cache::store(path, self.cache_key(code), present) {
+                        self.emit_warning(&format!("feature-probe: cannot update cache file {}: {}",
+                                                   path.display(), e));
+                    }
+                }
+                (Ok(present), false)
+            }
+            Err(e) => match self.unavailable {
+                _ if e.kind() == io::ErrorKind::Interrupted && self.is_cancelled()
+                                                 => (Err(e), false),
+                UnavailablePolicy::Panic         => (Err(e), false),
+                UnavailablePolicy::AssumeAbsent  => {
+                    self.warn_unavailable(&e, "assuming absent");
+                    (Ok(false), false)
+                }
+                UnavailablePolicy::AssumePresent => {
+                    self.warn_unavailable(&e, "assuming present");
+                    (Ok(true), false)
+                }
+                UnavailablePolicy::FromCache     => {
+                    let cached = match self.cache_file {
+                        Some(ref path) => cache::load(path)
+                            .map(|cache| cache.get(&self.cache_key(code)).cloned()),
+                        None           => Ok(None),
+                    };
+                    match cached {
+                        Ok(Some(present)) => {
+                            self.warn_unavailable(&e, "using the cached answer");
+                            (Ok(present), true)
+                        }
+                        Ok(None)          => (Err(e), false),
+                        Err(e)            => (Err(e), false),
+                    }
+                }
+            },
+        }
+    }
+
+    /// Warns that a probe couldn't be carried out and what is answered
+    /// instead.
+    fn warn_unavailable(&self, error: &io::Error, instead: &str) {
+        let name = self.label.as_ref().map_or(String::new(), |label| format!(" {}", label));
+        self.emit_warning(&format!("feature-probe: cannot carry out probe{} ({}); {}",
+                                   name, error, instead));
+    }
+
+    /// The answer given with `answer_in` for the current build context, if
+    /// any.
+    fn preset_answer(&self) -> Option<bool> {
+        if self.answers.is_empty() {
+            return None;
+        }
+
+        let context = self.context();
+        self.answers.iter()
+            .find(|answer| answer.0 == context)
+            .map(|answer| answer.1)
+    }
+
+    /// The key under which the answer for the given program is cached.
+    fn cache_key(&self, code: &str) -> u64 {
+        let mut fp = fingerprint::Fingerprinter::new();
+        fp.str(code)
+          .str(&format!("{:?}", self.profile()))
+          .str(&self.emit)
+          .option(self.target.as_ref(), |fp, target| { fp.os_str(target); })
+          .option(self.edition.as_ref(), |fp, edition| { fp.str(edition); })
+          .option(self.crate_type, |fp, crate_type| { fp.str(crate_type); });
+        for arg in &self.args {
+            fp.os_str(arg);
+        }
+        fp.finish()
+    }
+
+    /// Turns a failure caused by the configuration rather than by the probe
+    /// program into an error, so that it isn't mistaken for an absent
+    /// feature.
+    fn check_diagnostics(&self, present: bool, diagnostics: &str) -> io::Result<bool> {
+        if present {
+            return Ok(true);
+        }
+
+        let crates = ["std", "core"];
+        let missing = crates.iter()
+            .find(|krate| diagnostics.contains(&format!("can't find crate for `{}`", krate)));
+        if let Some(krate) = missing {
+            let target = match self.target {
+                Some(ref target) => target.to_string_lossy().into_owned(),
+                None             => self.host().unwrap_or_else(|_| String::from("<target>")),
+            };
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      format!("`{}` is not installed for target {}; \
+                                               try `rustup target add {}`",
+                                              krate, target, target)));
+        }
+
+        if diagnostics.contains("error loading target specification") {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("unknown target {:?}",
+                                              self.target.as_ref().map_or(OsStr::new(""),
+                                                                          |t| t.as_os_str()))));
+        }
+
+        Ok(false)
+    }
+
+    /// Probes for whether the whole program in the given file can be
+    /// compiled.
+    ///
+    /// Keeping nontrivial probe programs in files of their own makes them
+    /// easier to edit and to test. This also emits
+    /// `cargo:rerun-if-changed=PATH`, so that the build script reruns when
+    /// the file changes.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or the child `rustc` cannot be started or
+    /// communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if probe.probe_file("probes/int128.rs").unwrap() {
+    ///     println!("cargo:rustc-cfg=int128");
+    /// }
+    /// ```
+    pub fn probe_file<P: AsRef<Path>>(&self, path: P) -> io::Result<bool> {
+        let path = path.as_ref();
+        self.reporter.rerun_if_changed(path);
+
+        let mut code = String::new();
+        File::open(path)?.read_to_string(&mut code)?;
+        self.probe_result(&code)
+    }
+
+    /// Probes for whether a program made of several source files can be
+    /// compiled, for features that a single file can't show, such as module
+    /// files and `#[path]` attributes.
+    ///
+    /// Each file is given as a path relative to the crate root and its
+    /// contents, and the first file is the crate root. The files are written
+    /// to a fresh temporary directory and compiled together.
+    ///
+    /// # Errors
+    ///
+    /// If no files are given, a path is absolute or leaves the directory,
+    /// the files cannot be written, or the child `rustc` cannot be started
+    /// or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_files(&[
+    ///     ("main.rs",        "mod outer; fn main() { outer::inner::f(); }"),
+    ///     ("outer/mod.rs",   "pub mod inner;"),
+    ///     ("outer/inner.rs", "pub fn f() { }"),
+    /// ]).unwrap() );
+    /// assert!( ! probe.probe_files(&[
+    ///     ("main.rs",  "mod private; fn main() { private::f(); }"),
+    ///     ("private.rs", "fn f() { }"),
+    /// ]).unwrap() );
+    /// ```
+    pub fn probe_files(&self, files: &[(&str, &str)]) -> io::Result<bool> {
+        let root = match files.first() {
+            Some(root) => Path::new(root.0),
+            None       => return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                                    "no files to probe")),
+        };
+
+        let dir = temp::TempDir::new()?;
+        let mut code = String::new();
+
+        for &(name, contents) in files {
+            let relative = Path::new(name);
+            let escapes = relative.components().any(|component| match component {
+                Component::Normal(_) | Component::CurDir => false,
+                _                                        => true,
+            });
+            if escapes {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          format!("probe file `{}` is outside the crate", name)));
+            }
+
+            let path = dir.path().join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            compat::write(&path, contents)?;
+
+            code.push_str(&format!("// {}\n{}\n", name, contents));
+        }
+
+        let command = self.command_with_input(Some(dir.path()), Some(&dir.path().join(root)));
+        self.compile(command, &code)
+    }
+
+    /// Probes every `.rs` file in the given directory with
+    /// [`probe_file`](#method.probe_file), recording each result under the
+    /// file's stem.
+    ///
+    /// Files are probed in order of their names, and the directory itself is
+    /// passed to `cargo:rerun-if-changed` so that adding a file reruns the
+    /// build script. Pass the result to
+    /// [`ProbeReport::emit_cfgs`](struct.ProbeReport.html#method.emit_cfgs) to
+    /// turn `probes/int128.rs` into `cfg(int128)`.
+    ///
+    /// # Errors
+    ///
+    /// If the directory or any of its `.rs` files cannot be read, or the
+    /// child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// probe.probe_dir("probes").unwrap().emit_cfgs();
+    /// ```
+    pub fn probe_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<ProbeReport> {
+        let path = path.as_ref();
+        self.reporter.rerun_if_changed(path);
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.is_file() && file.extension() == Some(OsStr::new("rs")) {
+                files.push(file);
+            }
+        }
+        files.sort();
+
+        let mut report = ProbeReport::new();
+        for file in files {
+            let name = match file.file_stem().and_then(|stem| stem.to_str()) {
+                Some(stem) => stem.to_owned(),
+                None       => continue,
+            };
+            let start   = Instant::now();
+            let present = self.named(name.as_str()).probe_file(&file)?;
+            report.record_timed(name, present, start.elapsed());
+        }
+
+        Ok(report)
+    }
+
+    /// Probes for the given syntax feature.
+    ///
+    /// The probe program is compiled with the edition the feature requires,
+    /// so compilers that don't know that edition report the feature absent.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, SyntaxFeature};
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_syntax(SyntaxFeature::RawIdentifiers) );
+    /// assert!( probe.probe_syntax(SyntaxFeature::GenericAssociatedTypes) );
+    /// ```
+    pub fn probe_syntax(&self, feature: SyntaxFeature) -> bool {
+        let result = match (feature.edition(), self.edition.as_ref()) {
+            (Some(required), Some(edition)) if edition.as_str() >= required =>
+                self.probe_result(feature.code()),
+            (Some(required), _) =>
+                self.probe_result_with(feature.code(), ProbeOptions::new().edition(required)),
+            (None, _) => self.probe_result(feature.code()),
+        };
+
+        result.expect("Probe::probe_syntax")
+    }
+
+    /// Probes for every [`SyntaxFeature`](enum.SyntaxFeature.html), emitting
+    /// `cargo:rustc-cfg=NAME` through this probe's
+    /// [`reporter`](#method.reporter) for each one that is present.
+    ///
+    /// The names emitted are those returned by
+    /// [`SyntaxFeature::name`](enum.SyntaxFeature.html#method.name), such as
+    /// `let_else`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// probe.emit_all_syntax_cfgs();
+    /// ```
+    pub fn emit_all_syntax_cfgs(&self) {
+        self.emit_report_cfgs(&self.syntax_report());
+    }
+
+    /// Probes for every [`SyntaxFeature`](enum.SyntaxFeature.html), recording
+    /// the results under their [`name`](enum.SyntaxFeature.html#method.name)s.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let report = probe.syntax_report();
+    /// assert_eq!( report.get("raw_identifiers"), Some(true) );
+    /// ```
+    pub fn syntax_report(&self) -> ProbeReport {
+        let mut report = ProbeReport::new();
+
+        for &feature in SyntaxFeature::all() {
+            let start   = Instant::now();
+            let present = self.named(feature.name()).probe_syntax(feature);
+            report.record_timed(feature.name(), present, start.elapsed());
+        }
+
+        report
+    }
+
+    /// Probes whether the target supports atomic operations on integers of
+    /// the given width in bits.
+    ///
+    /// This checks the `target_has_atomic` configuration where the compiler
+    /// supports it, and otherwise falls back to probing for the corresponding
+    /// `AtomicU*` type. The answer is for the configured
+    /// [`target`](#method.target).
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_atomic_width(8) );
+    /// assert!( ! probe.probe_atomic_width(24) );
+    /// ```
+    pub fn probe_atomic_width(&self, bits: u32) -> bool {
+        match bits {
+            8 | 16 | 32 | 64 | 128 => (),
+            _ => return false,
+        }
+
+        self.probe(&format!("#[cfg(target_has_atomic = \"{}\")] fn main() {{ }}", bits))
+            || self.probe_type(&format!("::std::sync::atomic::AtomicU{}", bits))
+    }
+
+    /// Probes whether the target supports 64-bit atomics.
+    ///
+    /// Equivalent to `probe_atomic_width(64)`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if probe.has_atomic_u64() {
+    ///     println!("cargo:rustc-cfg=has_atomic_u64");
+    /// }
+    /// ```
+    pub fn has_atomic_u64(&self) -> bool {
+        self.probe_atomic_width(64)
+    }
+
+    /// Probes whether the given `std::arch` intrinsic exists and can be
+    /// called from a function with the named target feature enabled.
+    ///
+    /// The `intrinsic` is a path relative to `std::arch`, such as
+    /// `"x86_64::_mm256_abs_epi8"` or `"aarch64::vaddq_u8"`. The answer is for
+    /// the configured [`target`](#method.target), so it is false when the
+    /// feature or the architecture module does not exist there.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if cfg!(target_arch = "x86_64") {
+    ///     assert!(   probe.probe_intrinsic("avx2", "x86_64::_mm256_abs_epi8") );
+    ///     assert!( ! probe.probe_intrinsic("avx2", "x86_64::_mm256_no_such_thing") );
+    /// }
+    /// ```
+    pub fn probe_intrinsic(&self, target_feature: &str, intrinsic: &str) -> bool {
+        self.probe(&format!("#[target_feature(enable = \"{}\")] \
+                             #[allow(unused_unsafe)] \
+                             unsafe fn f() {{ let _ = ::std::arch::{}; }} \
+                             fn main() {{ let _ = f; }}",
+                            target_feature, intrinsic))
+    }
+
+    /// Probes whether the named target feature can be enabled with
+    /// `#[target_feature]` on the configured [`target`](#method.target).
+    ///
+    /// This says nothing about whether the feature is enabled by default; use
+    /// `cfg!(target_feature = "...")` in the compiled crate for that.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( ! probe.probe_target_feature("no-such-feature") );
+    /// ```
+    pub fn probe_target_feature(&self, target_feature: &str) -> bool {
+        self.probe(&format!("#[target_feature(enable = \"{}\")] \
+                             unsafe fn f() {{ }} \
+                             fn main() {{ let _ = f; }}",
+                            target_feature))
+    }
+
+    /// Returns the `rustc` command that probes run.
+    ///
+    /// The command reads the probe program from its standard input, which is
+    /// piped, or for compilers that can't (see
+    /// [`CompilerProfile`](enum.CompilerProfile.html)), from the file
+    /// `probe.rs` in the output directory. This is useful for auditing exactly what a probe will run, for
+    /// adding arguments or environment variables before running a program by
+    /// hand, and for reproducing a failing probe: the `Debug` format of the
+    /// command can be pasted into a shell. Probes themselves write to a fresh
+    /// subdirectory of the output directory, removed afterward, and each
+    /// command gets a unique `--crate-name` and `-C metadata`, so that
+    /// concurrent probes can't clobber each other's artifacts. In
+    /// [`hardened`](#method.hardened) mode, probes additionally run in a
+    /// fresh temporary directory, which is also passed as `--out-dir`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let mut child = probe.command().spawn().unwrap();
+    /// child.stdin.take().unwrap().write_all(b"fn main() { }").unwrap();
+    /// assert!( child.wait().unwrap().success() );
+    /// ```
+    pub fn command(&self) -> Command {
+        self.command_in(None)
+    }
+
+    /// A command that runs `rustc`, through the wrapper if there is one.
+    fn rustc_command(&self) -> Command {
+        match self.wrapper.split_first() {
+            Some((program, args)) => {
+                let mut command = spawn::program(program);
+                command.args(args).arg(&self.rustc);
+                command
+            }
+            None => spawn::program(&self.rustc),
+        }
+    }
+
+    fn command_in(&self, dir: Option<&Path>) -> Command {
+        self.command_with_input(dir, None)
+    }
+
+    /// Builds a command like `command_in`, but compiling the given file
+    /// rather than the program on standard input or in `INPUT_FILE`.
+    fn command_with_input(&self, dir: Option<&Path>, input: Option<&Path>) -> Command {
+        let profile = self.profile();
+        let out_dir = spawn::long_path(dir.unwrap_or_else(|| Path::new(&self.out_dir)));
+
+        let mut command = self.rustc_command();
+        match profile {
+            CompilerProfile::Rustc | CompilerProfile::ClippyDriver => {
+                command
+                    .arg("--out-dir")
+                    .arg(&out_dir)
+                    .arg(format!("--emit={}", self.emit));
+            }
+            CompilerProfile::Gccrs => {
+                command
+                    .arg("-frust-incomplete-and-experimental-compiler-do-not-use")
+                    .arg("-c")
+                    .arg("-o")
+                    .arg(out_dir.join("probe.o"));
+            }
+            CompilerProfile::Mrustc => {
+                command
+                    .arg("-o")
+                    .arg(out_dir.join("probe"));
+            }
+        }
+
+        if let Some(ref target) = self.target {
+            if profile != CompilerProfile::Gccrs {
+                command.arg("--target").arg(target);
+            }
+        }
+
+        if let Some(crate_type) = self.crate_type {
+            match profile {
+                CompilerProfile::Gccrs  => (),
+                CompilerProfile::Mrustc => { command.arg("--crate-type").arg(crate_type); }
+                _                       => { command.arg(format!("--crate-type={}", crate_type)); }
+            }
+        }
+
+        // Compilers without `--edition` only know the 2015 edition, so
+        // leaving the flag out gives them the only answer they can.
+        let edition = match self.edition {
+            Some(ref edition) => Some(edition.as_str()),
+            None if self.hardened => Some("2015"),
+            None => None,
+        };
+        if let Some(edition) = edition {
+            match profile {
+                CompilerProfile::Gccrs  => { command.arg(format!("-frust-edition={}", edition)); }
+                CompilerProfile::Mrustc => { command.arg("--edition").arg(edition); }
+                _ => if self.rustc_supports(EDITION_FLAG_VERSION) {
+                    command.arg(format!("--edition={}", edition));
+                },
+            }
+        }
+
+        if profile.accepts_rustc_flags() {
+            if self.hardened {
+                command.arg("--crate-name=probe");
+            } else if !self.args.iter().any(|arg| arg.to_string_lossy().starts_with("--crate-name")) {
+                // Unique names keep the artifacts and symbols of concurrent
+                // probes apart.
+                let name = temp::unique_name("probe");
+                command.arg(format!("--crate-name={}", name))
+                       .arg("-C").arg(format!("metadata={}", name));
+            }
+        }
+
+        if self.hardened {
+            command.env_clear();
+            for var in &self.env_allowlist {
+                if let Some(value) = env::var_os(var) {
+                    command.env(var, value);
+                }
+            }
+        }
+
+        if self.sanitize_env {
+            for var in SANITIZED_ENV {
+                command.env_remove(var);
+            }
+            for &(var, value) in NORMALIZED_ENV {
+                command.env(var, value);
+            }
+        }
+
+        command.args(&self.args);
+        for (var, value) in &self.envs {
+            command.env(var, value);
+        }
+
+        // Checking `cfg`s is enabled by any `--check-cfg`, and then the ones
+        // set by `--cfg` must be declared too, or probe programs using them
+        // get warnings.
+        if profile.accepts_rustc_flags() && self.args.iter().any(|arg| is_check_cfg(arg)) {
+            let check_cfgs = check_cfgs(&self.args);
+            if !check_cfgs.is_empty() && self.rustc_supports(CHECK_CFG_VERSION) {
+                for check_cfg in check_cfgs {
+                    command.arg(format!("--check-cfg={}", check_cfg));
+                }
+            }
+        }
+
+        if self.hardened {
+            if let Some(dir) = dir {
+                command.current_dir(dir);
+            }
+        }
+
+        if let Some(input) = input {
+            command
+                .arg(spawn::long_path(input))
+                .stdin(Stdio::null());
+        } else if profile.reads_stdin() {
+            command
+                .arg("-")
+                .stdin(Stdio::piped());
+        } else {
+            command
+                .arg(out_dir.join(INPUT_FILE))
+                .stdin(Stdio::null());
+        }
+
+        command
+    }
+
+    /// Probes whether the given items compile as a library crate, which
+    /// unlike a binary needs no `main` function.
+    fn probe_items_as_lib(&self, items: &str) -> bool {
+        let mut probe = self.clone();
+        probe.crate_type = Some("lib");
+        probe.probe(items)
+    }
+
+    /// Prepares a compilation of the program with the probe's timeout,
+    /// limits and cancellation.
+    fn compilation<'a>(&self, command: Command, code: &'a str) -> backend::Compilation<'a> {
+        backend::compilation(command, code, self.timeout, self.limits, self.cancel.clone())
+    }
+
+    /// Compiles the program afresh, keeping the command and diagnostics.
+    fn compile_capturing(&self, code: &str, command: &mut String, diagnostics: &mut String)
+                          -> io::Result<bool> {
+        if self.hardened {
+            self.check_hardened_rustc()?;
+        }
+
+        let dir = temp::TempDir::new()?;
+        if !self.profile().reads_stdin() {
+            compat::write(dir.path().join(INPUT_FILE), code)?;
+        }
+
+        let mut prepared = self.command_in(Some(dir.path()));
+        if let Some(ref hook) = self.before_spawn {
+            let mut hook = hook.lock().unwrap();
+            let hook = &mut *hook;
+            hook(&mut prepared);
+        }
+
+        let mut compilation = self.compilation(prepared, code);
+        *command = format!("{:?}", compilation.command());
+
+        let result = self.backend.compile(&mut compilation);
+        *diagnostics = compilation.diagnostics().to_owned();
+        result.and_then(|present| self.check_diagnostics(present, compilation.diagnostics()))
+    }
+
+    /// Runs `rustc --print WHAT` for the probe's target and arguments.
+    fn print_info(&self, what: &str) -> io::Result<String> {
+        let mut args = vec![OsString::from("--print"), OsString::from(what)];
+        if let Some(ref target) = self.target {
+            args.push(OsString::from("--target"));
+            args.push(target.clone());
+        }
+        args.extend(self.args.iter().cloned());
+
+        self.run_rustc(&args)
+    }
+
+    /// Whether `fn main() { }` compiles without diagnostics given the flags.
+    fn accepts_flags(&self, flags: &[&str]) -> io::Result<bool> {
+        let mut probe = self.clone();
+        probe.args(flags.iter().cloned());
+
+        let mut command = String::new();
+        let mut diagnostics = String::new();
+        let compiled = probe.compile_capturing("fn main() { }", &mut command, &mut diagnostics)?;
+        Ok(compiled && diagnostics.trim().is_empty())
+    }
+
+    /// Refuses, for hardened mode, a compiler that isn't `rustc` or that runs
+    /// through a wrapper. Every path that starts the compiler goes through
+    /// `answer`, `compile_capturing` or `run_rustc`, which check this.
+    fn check_hardened_rustc(&self) -> io::Result<()> {
+        if !self.wrapper.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("hardened probe refuses wrapper {:?}",
+                                              self.wrapper)));
+        }
+
+        let stem = Path::new(&self.rustc).file_stem();
+        if stem == Some(OsStr::new("rustc")) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               format!("hardened probe refuses compiler {:?}: not rustc",
+                                       self.rustc)))
+        }
+    }
+}
+
+impl fmt::Debug for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Probe")
+            .field("rustc",         &self.rustc)
+            .field("wrapper",       &self.wrapper)
+            .field("profile",       &self.profile)
+            .field("out_dir",       &self.out_dir)
+            .field("target",        &self.target)
+            .field("edition",       &self.edition)
+            .field("crate_type",    &self.crate_type)
+            .field("emit",          &self.emit)
+            .field("cfg_prefix",    &self.cfg_prefix)
+            .field("args",          &self.args)
+            .field("envs",          &self.envs)
+            .field("hardened",      &self.hardened)
+            .field("env_allowlist", &self.env_allowlist)
+            .field("sanitize_env",  &self.sanitize_env)
+            .field("timeout",       &self.timeout)
+            .field("limits",        &self.limits)
+            .field("context",       &self.context)
+            .field("answers",       &self.answers)
+            .field("unavailable",   &self.unavailable)
+            .field("mismatch",      &self.mismatch)
+            .field("cache_file",    &self.cache_file)
+            .field("share_answers", &self.share_answers)
+            .field("auto_check",    &self.auto_check)
+            .field("label",         &self.label)
+            .field("cancel",        &self.cancel)
+            .field("progress",      &self.progress.as_ref().map(|tracker| tracker.output()))
+            .field("runner",        &self.runner)
+            .finish()
+    }
+}
+
+impl Default for Probe {
+    fn default() -> Self {
+        Probe::new()
+    }
+}
+
+/// Whether the argument is, or starts, a `--check-cfg` flag.
+fn is_check_cfg(arg: &OsStr) -> bool {
+    let arg = arg.to_string_lossy();
+    arg == "--check-cfg" || arg.starts_with("--check-cfg=")
+}
+
+/// Returns a `--check-cfg` declaration for each `--cfg` in the arguments.
+fn check_cfgs(args: &[OsString]) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut args = args.iter().map(|arg| arg.to_string_lossy());
+
+    while let Some(arg) = args.next() {
+        let spec = if arg == "--cfg" {
+            match args.next() {
+                Some(spec) => spec.into_owned(),
+                None       => break,
+            }
+        } else if arg.starts_with("--cfg=") {
+            arg["--cfg=".len() ..].to_owned()
+        } else {
+            continue;
+        };
+
+        let mut parts = spec.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim();
+        match parts.next() {
+            Some(value) => result.push(format!("cfg({}, values({}))", name, value.trim())),
+            None        => result.push(format!("cfg({})", name)),
+        }
+    }
+
+    result
+}
+
+/// Whether a failure to start a process looks like a sandbox's doing rather
+/// than a problem with the file.
+fn is_sandbox_denial(error: &io::Error) -> bool {
+    // `EPERM`, where a file that isn't executable gives `EACCES`.
+    cfg!(unix) && error.raw_os_error() == Some(1)
+}
+
+/// Emits `cargo:rustc-cfg=NAME`, enabling `#[cfg(NAME)]` in the crate being
+/// built.
+///
+/// # Examples
+///
+/// ```
+/// feature_probe::emit_cfg("int_128");
+/// ```
+pub fn emit_cfg(name: &str) {
+    StdoutReporter.cfg(name);
+}
+
+/// Emits `cargo:rustc-cfg=NAME="VALUE"`, enabling `#[cfg(NAME = "VALUE")]`
+/// in the crate being built. The value is escaped as a Rust string literal.
+///
+/// # Examples
+///
+/// ```
+/// feature_probe::emit_cfg_value("api_level", "3");
+/// ```
+pub fn emit_cfg_value(name: &str, value: &str) {
+    StdoutReporter.cfg_value(name, value);
+}
+
+/// Emits `cargo:rustc-check-cfg`, declaring the expected values of a
+/// configuration option so that Cargo doesn't warn about it. See
+/// [`Reporter::check_cfg`](trait.Reporter.html#method.check_cfg).
+///
+/// # Examples
+///
+/// ```
+/// feature_probe::emit_check_cfg("api_level", &["1", "2", "3"]);
+/// ```
+pub fn emit_check_cfg(name: &str, values: &[&str]) {
+    StdoutReporter.check_cfg(name, values);
+}
+
+/// Emits `cargo:rerun-if-changed=PATH`, so that the build script reruns when
+/// the given file or directory changes.
+///
+/// # Examples
+///
+/// ```
+/// feature_probe::emit_rerun_if_changed("probes");
+/// ```
+pub fn emit_rerun_if_changed<P: AsRef<Path>>(path: P) {
+    StdoutReporter.rerun_if_changed(path.as_ref());
+}
+
+/// Emits `cargo:KEY=VALUE` metadata for a crate with a `links` key.
+///
+/// Build scripts of crates that depend directly on this one can read the
+/// value from the environment variable `DEP_LINKS_KEY`, where `LINKS` is the
+/// `links` value and `KEY` is uppercased with dashes replaced by underscores.
+///
+/// Where Cargo accepts it, the directive is written
+/// `cargo::metadata=KEY=VALUE`.
+///
+/// # Panics
+///
+/// If the key contains `=` or `:` or either argument contains a newline,
+/// since that would corrupt the directive, or if the key is one that Cargo
+/// reserves for its own directives, such as `rustc-cfg`.
+///
+/// # Examples
+///
+/// ```
+/// feature_probe::emit_metadata("has_i128", "1");
+/// ```
+pub fn emit_metadata(key: &str, value: &str) {
+    StdoutReporter.metadata(key, value);
+}
+
+/// Emits `cargo:warning=MESSAGE`, with a directive for each line of the
+/// message.
+///
+/// # Examples
+///
+/// ```
+/// feature_probe::emit_warning("no SIMD; using the portable fallback");
+/// ```
+pub fn emit_warning(message: &str) {
+    StdoutReporter.warning(message);
+}
+
+/// Emits an error that fails the build once the build script is done, as
+/// `cargo::error` with Cargo 1.84 or later, unless the package's
+/// `rust-version` forbids that syntax, and otherwise as a warning starting
+/// with `error:`, in which case the build script should fail itself.
+///
+/// # Examples
+///
+/// ```
+/// # if false {
+/// feature_probe::emit_error("this crate needs 128-bit integers");
+/// # }
+/// ```
+pub fn emit_error(message: &str) {
+    StdoutReporter.error(message);
+}
+
+/// Quotes a `cfg` value as a Rust string literal.
+fn cfg_string(value: &str) -> String {
+    let escaped: String = value.chars().flat_map(char::escape_default).collect();
+    format!("\"{}\"", escaped)
 }
 
 fn env_var_or(var: &str, default: &str) -> OsString {
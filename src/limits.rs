@@ -0,0 +1,255 @@
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+use std::time::Duration;
+
+/// Resource limits for a probe's `rustc` process.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ResourceLimits {
+    /// The maximum CPU time.
+    pub cpu_time: Option<Duration>,
+    /// The maximum memory, in bytes.
+    pub memory:   Option<u64>,
+    /// Whether to run at a lower CPU and I/O priority, where supported.
+    pub low_priority: bool,
+}
+
+impl ResourceLimits {
+    /// Whether no limit is set.
+    pub fn is_unlimited(&self) -> bool {
+        self.cpu_time.is_none() && self.memory.is_none()
+    }
+
+    /// Whether nothing needs to be done to the process.
+    fn is_default(&self) -> bool {
+        self.is_unlimited() && !self.low_priority
+    }
+
+    /// Arranges for the limits to apply to the process spawned from
+    /// `command`, where that has to happen before spawning.
+    pub fn apply_before_spawn(&self, command: &mut Command) -> io::Result<()> {
+        if self.is_default() {
+            Ok(())
+        } else {
+            imp::apply_before_spawn(self, command)
+        }
+    }
+
+    /// Applies the limits to a spawned process, where that has to happen
+    /// after spawning. The returned guard must live as long as the process.
+    pub fn apply_after_spawn(&self, child: &Child) -> io::Result<Guard> {
+        if self.is_default() {
+            Ok(Guard(None))
+        } else {
+            imp::apply_after_spawn(self, child).map(|guard| Guard(Some(guard)))
+        }
+    }
+}
+
+/// Keeps platform resources needed to enforce limits alive.
+pub struct Guard(Option<imp::Guard>);
+
+impl Guard {
+    /// Whether the process, having exited with the status, was stopped by a
+    /// limit in a way that its status doesn't show.
+    pub fn exceeded(&self, status: &ExitStatus) -> bool {
+        self.0.as_ref().map_or(false, |guard| guard.exceeded(status))
+    }
+}
+
+#[cfg(all(feature = "resource-limits", unix,
+          any(target_os = "linux", target_os = "android", target_os = "macos"),
+          not(any(target_arch = "mips", target_arch = "mips64"))))]
+#[path = "limits_unix.rs"]
+mod imp;
+
+#[cfg(all(feature = "resource-limits", windows))]
+mod imp {
+    use std::io;
+    use std::mem;
+    use std::os::raw::c_void;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::{Child, Command, ExitStatus};
+    use std::ptr;
+
+    use super::ResourceLimits;
+
+    type Handle = *mut c_void;
+
+    const JOB_OBJECT_BASIC_ACCOUNTING_INFORMATION_CLASS: i32 = 1;
+    const JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS: i32 = 9;
+    const JOB_OBJECT_LIMIT_PROCESS_TIME: u32               = 0x0000_0002;
+    const JOB_OBJECT_LIMIT_PROCESS_MEMORY: u32             = 0x0000_0100;
+    const JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE: u32          = 0x0000_2000;
+    const BELOW_NORMAL_PRIORITY_CLASS: u32                 = 0x0000_4000;
+
+    #[repr(C)]
+    struct BasicLimitInformation {
+        per_process_user_time_limit: i64,
+        per_job_user_time_limit:     i64,
+        limit_flags:                 u32,
+        minimum_working_set_size:    usize,
+        maximum_working_set_size:    usize,
+        active_process_limit:        u32,
+        affinity:                    usize,
+        priority_class:              u32,
+        scheduling_class:            u32,
+    }
+
+    #[repr(C)]
+    struct IoCounters {
+        counts: [u64; 6],
+    }
+
+    #[repr(C)]
+    struct ExtendedLimitInformation {
+        basic:                    BasicLimitInformation,
+        io_info:                  IoCounters,
+        process_memory_limit:     usize,
+        job_memory_limit:         usize,
+        peak_process_memory_used: usize,
+        peak_job_memory_used:     usize,
+    }
+
+    #[repr(C)]
+    struct BasicAccountingInformation {
+        total_user_time:               i64,
+        total_kernel_time:             i64,
+        this_period_total_user_time:   i64,
+        this_period_total_kernel_time: i64,
+        total_page_fault_count:        u32,
+        total_processes:               u32,
+        active_processes:              u32,
+        total_terminated_processes:    u32,
+    }
+
+    extern "system" {
+        fn CreateJobObjectW(attributes: *mut c_void, name: *const u16) -> Handle;
+        fn SetInformationJobObject(job: Handle, class: i32, info: *mut c_void, len: u32) -> i32;
+        fn QueryInformationJobObject(job: Handle, class: i32, info: *mut c_void, len: u32,
+                                     returned: *mut u32) -> i32;
+        fn AssignProcessToJobObject(job: Handle, process: Handle) -> i32;
+        fn CloseHandle(handle: Handle) -> i32;
+        fn SetPriorityClass(process: Handle, class: u32) -> i32;
+    }
+
+    /// Owns the job object, if any; closing it kills the process.
+    pub struct Guard {
+        job:            Option<Handle>,
+        memory_limited: bool,
+    }
+
+    impl Guard {
+        /// The job counts the processes it kills for exceeding the time
+        /// limit. A process that runs out of memory fails to allocate
+        /// instead, and aborts with an `NTSTATUS` error code.
+        pub fn exceeded(&self, status: &ExitStatus) -> bool {
+            let job = match self.job {
+                Some(job) => job,
+                None      => return false,
+            };
+
+            let killed = unsafe {
+                let mut info: BasicAccountingInformation = mem::zeroed();
+                QueryInformationJobObject(job,
+                                          JOB_OBJECT_BASIC_ACCOUNTING_INFORMATION_CLASS,
+                                          &mut info as *mut _ as *mut c_void,
+                                          mem::size_of::<BasicAccountingInformation>() as u32,
+                                          ptr::null_mut()) != 0
+                    && info.total_terminated_processes > 0
+            };
+            let aborted = self.memory_limited
+                && status.code().map_or(false, |code| code as u32 >= 0xC000_0000);
+
+            killed || aborted
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            if let Some(job) = self.job {
+                unsafe { CloseHandle(job); }
+            }
+        }
+    }
+
+    pub fn apply_before_spawn(_: &ResourceLimits, _: &mut Command) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub fn apply_after_spawn(limits: &ResourceLimits, child: &Child) -> io::Result<Guard> {
+        if limits.low_priority {
+            // Lowering priority is best effort, so failure is ignored.
+            unsafe { SetPriorityClass(child.as_raw_handle() as Handle, BELOW_NORMAL_PRIORITY_CLASS); }
+        }
+        if limits.is_unlimited() {
+            return Ok(Guard { job: None, memory_limited: false });
+        }
+
+        unsafe {
+            let job = CreateJobObjectW(ptr::null_mut(), ptr::null());
+            if job.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let guard = Guard { job: Some(job), memory_limited: limits.memory.is_some() };
+
+            let mut info: ExtendedLimitInformation = mem::zeroed();
+            info.basic.limit_flags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            if let Some(cpu_time) = limits.cpu_time {
+                info.basic.limit_flags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+                // In units of 100 nanoseconds.
+                info.basic.per_process_user_time_limit =
+                    (cpu_time.as_secs() as i64) * 10_000_000
+                        + i64::from(cpu_time.subsec_nanos() / 100);
+            }
+            if let Some(memory) = limits.memory {
+                info.basic.limit_flags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+                info.process_memory_limit = memory as usize;
+            }
+
+            if SetInformationJobObject(job,
+                                       JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS,
+                                       &mut info as *mut _ as *mut c_void,
+                                       mem::size_of::<ExtendedLimitInformation>() as u32) == 0
+                || AssignProcessToJobObject(job, child.as_raw_handle() as Handle) == 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(guard)
+        }
+    }
+}
+
+#[cfg(not(all(feature = "resource-limits",
+              any(windows,
+                  all(unix, any(target_os = "linux", target_os = "android", target_os = "macos"),
+                      not(any(target_arch = "mips", target_arch = "mips64")))))))]
+mod imp {
+    use std::io;
+    use std::process::{Child, Command, ExitStatus};
+
+    use super::ResourceLimits;
+
+    pub struct Guard;
+
+    impl Guard {
+        pub fn exceeded(&self, _: &ExitStatus) -> bool {
+            false
+        }
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Other,
+                       "resource limits are not supported on this platform, \
+                        or without the `resource-limits` feature")
+    }
+
+    // Priority is only a preference, so it is quietly not lowered here.
+    pub fn apply_before_spawn(limits: &ResourceLimits, _: &mut Command) -> io::Result<()> {
+        if limits.is_unlimited() { Ok(()) } else { Err(unsupported()) }
+    }
+
+    pub fn apply_after_spawn(limits: &ResourceLimits, _: &Child) -> io::Result<Guard> {
+        if limits.is_unlimited() { Ok(Guard) } else { Err(unsupported()) }
+    }
+}
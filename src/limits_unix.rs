@@ -0,0 +1,94 @@
+use std::convert::TryFrom;
+use std::io;
+use std::os::raw::{c_int, c_long};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus};
+
+use super::ResourceLimits;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+type RlimT = ::std::os::raw::c_ulong;
+#[cfg(target_os = "macos")]
+type RlimT = u64;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const RLIMIT_AS: i32 = 9;
+#[cfg(target_os = "macos")]
+const RLIMIT_AS: i32 = 5;
+const RLIMIT_CPU: i32 = 0;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const SYS_IOPRIO_SET: Option<c_long> = Some(251);
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+const SYS_IOPRIO_SET: Option<c_long> = Some(30);
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+const SYS_IOPRIO_SET: Option<c_long> = None;
+
+const IOPRIO_WHO_PROCESS: c_int = 1;
+/// The best-effort class at its lowest level, 7.
+const IOPRIO_LOW: c_int = (2 << 13) | 7;
+
+#[repr(C)]
+struct Rlimit {
+    rlim_cur: RlimT,
+    rlim_max: RlimT,
+}
+
+extern "C" {
+    fn setrlimit(resource: i32, rlim: *const Rlimit) -> i32;
+    fn nice(increment: c_int) -> c_int;
+    fn syscall(number: c_long, ...) -> c_long;
+}
+
+pub struct Guard;
+
+impl Guard {
+    /// A process killed for exceeding a limit dies of a signal, which is
+    /// already reported as such.
+    pub fn exceeded(&self, _: &ExitStatus) -> bool {
+        false
+    }
+}
+
+#[clippy::msrv = "1.34.0"]
+fn set(resource: i32, value: u64) -> io::Result<()> {
+    let value = RlimT::try_from(value).unwrap_or(RlimT::max_value());
+    let limit = Rlimit { rlim_cur: value, rlim_max: value };
+    if unsafe { setrlimit(resource, &limit) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[clippy::msrv = "1.34.0"]
+pub fn apply_before_spawn(limits: &ResourceLimits, command: &mut Command) -> io::Result<()> {
+    let limits = *limits;
+    // Safety: `setrlimit`, `nice` and `syscall` are async-signal-safe, and
+    // `set` does not allocate.
+    unsafe {
+        command.pre_exec(move || {
+            if limits.low_priority {
+                // Lowering priority is best effort, so failures are ignored.
+                nice(10);
+                if let Some(number) = SYS_IOPRIO_SET {
+                    syscall(number, IOPRIO_WHO_PROCESS, 0, IOPRIO_LOW);
+                }
+            }
+            if let Some(cpu_time) = limits.cpu_time {
+                // Rounded up to whole seconds, and at least one.
+                let seconds = cpu_time.as_secs() + if cpu_time.subsec_nanos() > 0 { 1 } else { 0 };
+                set(RLIMIT_CPU, seconds.max(1))?;
+            }
+            if let Some(memory) = limits.memory {
+                set(RLIMIT_AS, memory)?;
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+pub fn apply_after_spawn(_: &ResourceLimits, _: &Child) -> io::Result<Guard> {
+    Ok(Guard)
+}
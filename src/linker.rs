@@ -0,0 +1,147 @@
+use std::io;
+use std::path::Path;
+
+use super::Probe;
+
+/// The command-line syntax a linker takes, which decides how link arguments
+/// must be spelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LinkerFlavor {
+    /// A C compiler driving the linker, such as `cc`, `gcc` or `clang`,
+    /// which takes linker options as `-Wl,--as-needed`. Linkers that aren't
+    /// recognized are assumed to be of this kind.
+    Gcc,
+    /// A linker run directly, such as `ld`, `ld.lld`, `rust-lld` or
+    /// `wasm-ld`, which takes options as `--as-needed`.
+    Ld,
+    /// Microsoft's `link.exe` or LLVM's `lld-link`, which take options as
+    /// `/OPT:REF`.
+    Msvc,
+}
+
+impl LinkerFlavor {
+    /// Classifies a linker by its program name.
+    fn from_linker(linker: &str) -> Self {
+        let stem = Path::new(linker).file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if stem == "link" || stem.ends_with("lld-link") {
+            LinkerFlavor::Msvc
+        } else if stem == "ld" || stem.starts_with("ld.") || stem.ends_with("-ld")
+                || stem.ends_with("lld") || stem.starts_with("ld64") {
+            LinkerFlavor::Ld
+        } else {
+            LinkerFlavor::Gcc
+        }
+    }
+}
+
+impl Probe {
+    /// Determines the linker `rustc` runs for the probe's target, as it
+    /// names it, such as `cc`, `link.exe` or `rust-lld`.
+    ///
+    /// The probe links a program with an input file that doesn't exist and
+    /// reads the linker from the resulting error, so this works whether or
+    /// not the linker is installed.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with, or with
+    /// kind `InvalidData` if the probe fails without naming a linker, such as
+    /// when the target's standard library isn't installed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if cfg!(all(target_os = "linux", target_env = "gnu")) {
+    ///     assert_eq!( probe.linker().unwrap(), "cc" );
+    /// }
+    /// ```
+    pub fn linker(&self) -> io::Result<String> {
+        let mut probe = self.clone();
+        probe.emit = String::from("link");
+        probe.crate_type = None;
+        probe.arg("-C").arg("link-arg=feature-probe-missing-input.o");
+
+        let mut command = String::new();
+        let mut diagnostics = String::new();
+        probe.compile_capturing("fn main() { }", &mut command, &mut diagnostics)?;
+
+        diagnostics.lines()
+            .filter_map(|line| {
+                let rest = if line.starts_with("error: linking with `") {
+                    &line["error: linking with `".len()..]
+                } else if line.starts_with("error: linker `") {
+                    &line["error: linker `".len()..]
+                } else {
+                    return None;
+                };
+                rest.find('`').map(|end| rest[..end].to_owned())
+            })
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+                                          "cannot determine the linker"))
+    }
+
+    /// Determines the command-line syntax of the [`linker`](#method.linker)
+    /// for the probe's target, so that `-sys` crates can choose how to spell
+    /// link arguments.
+    ///
+    /// # Errors
+    ///
+    /// As for [`linker`](#method.linker).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{LinkerFlavor, Probe};
+    ///
+    /// let probe = Probe::new();
+    /// let as_needed = match probe.linker_flavor().unwrap() {
+    ///     LinkerFlavor::Gcc  => Some("-Wl,--as-needed"),
+    ///     LinkerFlavor::Ld   => Some("--as-needed"),
+    ///     LinkerFlavor::Msvc => None,
+    /// };
+    /// if let Some(arg) = as_needed {
+    ///     assert!( probe.probe_link_arg(arg) );
+    /// }
+    /// ```
+    pub fn linker_flavor(&self) -> io::Result<LinkerFlavor> {
+        self.linker().map(|linker| LinkerFlavor::from_linker(&linker))
+    }
+
+    /// Probes whether the linker for the probe's target accepts an
+    /// argument, as passed with `-C link-arg`.
+    ///
+    /// The probe is linked, so this fails if no linker is installed for the
+    /// target. An argument the linker accepts only with a warning counts as
+    /// not accepted.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if cfg!(all(target_os = "linux", target_env = "gnu")) {
+    ///     assert!(   probe.probe_link_arg("-Wl,--as-needed") );
+    ///     assert!( ! probe.probe_link_arg("-Wl,--no-such-option") );
+    /// }
+    /// ```
+    pub fn probe_link_arg(&self, arg: &str) -> bool {
+        let mut probe = self.clone();
+        probe.emit = String::from("link");
+        probe.crate_type = None;
+        probe.accepts_flags(&["-C", &format!("link-arg={}", arg)])
+            .expect("Probe::probe_link_arg")
+    }
+}
@@ -0,0 +1,67 @@
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+
+use super::{CompilerProfile, Probe};
+
+impl Probe {
+    /// Probes whether Clippy has a lint of the given name, with or without
+    /// the `clippy::` prefix, so that attributes such as
+    /// `#[allow(clippy::NAME)]` can be left out where they would warn about
+    /// an unknown lint. Lints that have been renamed or removed count as
+    /// absent.
+    ///
+    /// This compiles with the `clippy-driver` that `cargo clippy` is running
+    /// the build with, if it is, and otherwise with the one installed
+    /// alongside `rustc`.
+    ///
+    /// # Errors
+    ///
+    /// If `clippy-driver` cannot be found, as when the toolchain has no
+    /// Clippy, or cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if let Ok(present) = probe.probe_clippy_lint("clippy::needless_borrow") {
+    ///     assert!( present );
+    ///     assert!( ! probe.probe_clippy_lint("no_such_lint").unwrap() );
+    /// }
+    /// ```
+    pub fn probe_clippy_lint(&self, lint: &str) -> io::Result<bool> {
+        let lint = if lint.starts_with("clippy::") { &lint["clippy::".len() ..] } else { lint };
+
+        let mut probe = self.clone();
+        probe.rustc = self.clippy_driver()?.into_os_string();
+        probe.wrapper.clear();
+        probe.profile = Some(CompilerProfile::ClippyDriver);
+        probe.args(&["-D", "unknown_lints", "-D", "renamed_and_removed_lints"]);
+        probe.probe_result(&format!("#![allow(clippy::{})] fn main() {{ }}", lint))
+    }
+
+    /// Finds the `clippy-driver` to probe lints with.
+    fn clippy_driver(&self) -> io::Result<PathBuf> {
+        // `cargo clippy` runs builds with `clippy-driver` as the wrapper.
+        if let Some(wrapper) = env::var_os("RUSTC_WORKSPACE_WRAPPER") {
+            if CompilerProfile::detect(&wrapper) == CompilerProfile::ClippyDriver {
+                return Ok(PathBuf::from(wrapper));
+            }
+        }
+
+        let mut name = OsString::from("clippy-driver");
+        name.push(env::consts::EXE_SUFFIX);
+        let driver = self.sysroot()?.join("bin").join(name);
+
+        if driver.is_file() {
+            Ok(driver)
+        } else {
+            Err(io::Error::new(io::ErrorKind::NotFound,
+                               "clippy-driver is not installed; \
+                                try `rustup component add clippy`"))
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use compat;
+
+/// Determines the edition of the crate whose build script is running, from
+/// its Cargo.toml, or `None` outside of a build script or if the manifest
+/// doesn't say.
+pub fn edition() -> Option<String> {
+    // Cargo sets `OUT_DIR` for build scripts, but not for tests.
+    try_opt!(env::var_os("OUT_DIR"));
+    let dir = PathBuf::from(try_opt!(env::var_os("CARGO_MANIFEST_DIR")));
+    let manifest = try_opt!(compat::read_to_string(dir.join("Cargo.toml")).ok());
+
+    match try_opt!(lookup(&manifest, "package", "edition")) {
+        Value::Str(edition) => Some(edition),
+        Value::Workspace    => workspace_edition(&dir),
+    }
+}
+
+/// Finds the edition that a workspace member inherits, from the nearest
+/// enclosing manifest with a `[workspace]` table.
+fn workspace_edition(dir: &Path) -> Option<String> {
+    for dir in compat::ancestors(dir).skip(1) {
+        let manifest = match compat::read_to_string(dir.join("Cargo.toml")) {
+            Ok(manifest) => manifest,
+            Err(_)       => continue,
+        };
+
+        if manifest.lines().any(|line| line.trim() == "[workspace]") {
+            return match try_opt!(lookup(&manifest, "workspace.package", "edition")) {
+                Value::Str(edition) => Some(edition),
+                Value::Workspace    => None,
+            };
+        }
+    }
+
+    None
+}
+
+/// The value of a manifest key, as far as editions go.
+enum Value {
+    /// A string, such as `"2021"`.
+    Str(String),
+    /// Inherited from the workspace, as with `edition.workspace = true`.
+    Workspace,
+}
+
+/// Looks up a key in a table of a manifest.
+///
+/// This understands only as much TOML as edition keys are written with in
+/// practice: one key per line, in a table header or with dotted keys.
+fn lookup(manifest: &str, table: &str, key: &str) -> Option<Value> {
+    let mut current = String::new();
+
+    for line in manifest.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            current = line.trim_matches(|c| c == '[' || c == ']').trim().to_owned();
+            continue;
+        }
+
+        let mut parts = line.splitn(2, '=');
+        let (name, value) = match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) => (name.trim(), value.trim()),
+            _                         => continue,
+        };
+        let value = value.split('#').next().unwrap_or("").trim();
+
+        let dotted = format!("{}.workspace", key);
+        let full   = format!("{}.{}", table, key);
+
+        if (current == table && name == key) || (current.is_empty() && name == full) {
+            if value.starts_with('{') {
+                if value.contains("workspace") {
+                    return Some(Value::Workspace);
+                }
+            } else if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                return Some(Value::Str(value[1 .. value.len() - 1].to_owned()));
+            }
+        } else if current == table && name == dotted && value == "true" {
+            return Some(Value::Workspace);
+        }
+    }
+
+    None
+}
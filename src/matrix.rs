@@ -0,0 +1,144 @@
+use std::cmp;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::{outcome, Probe, ProbeOutcome, ProbeReport, SupportTable};
+
+/// The outcomes of a set of named probes on each of several toolchains, from
+/// [`Probe::probe_matrix`](struct.Probe.html#method.probe_matrix).
+///
+/// Every cell has an outcome: probes that couldn't be carried out, including
+/// all those for a toolchain that isn't installed, are `Unknown`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProbeMatrix {
+    toolchains: Vec<String>,
+    names:      Vec<String>,
+    outcomes:   Vec<ProbeOutcome>,
+}
+
+impl ProbeMatrix {
+    /// The toolchains, in the order given.
+    pub fn toolchains(&self) -> &[String] {
+        &self.toolchains
+    }
+
+    /// The probe names, in the order given.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The outcome of the named probe on the toolchain, or `None` if the
+    /// matrix has no such toolchain or probe.
+    pub fn get(&self, toolchain: &str, name: &str) -> Option<ProbeOutcome> {
+        let row = try_opt!(self.toolchains.iter().position(|t| t == toolchain));
+        let column = try_opt!(self.names.iter().position(|n| n == name));
+        Some(self.outcomes[row * self.names.len() + column])
+    }
+
+    /// The known outcomes on the toolchain as a report, leaving out those
+    /// that are `Unknown`, or `None` if the matrix has no such toolchain.
+    pub fn report(&self, toolchain: &str) -> Option<ProbeReport> {
+        let row = try_opt!(self.toolchains.iter().position(|t| t == toolchain));
+        let outcomes = &self.outcomes[row * self.names.len()..(row + 1) * self.names.len()];
+
+        let mut report = ProbeReport::new();
+        for (name, outcome) in self.names.iter().zip(outcomes) {
+            if let Some(present) = outcome.to_option() {
+                report.record(name.as_str(), present);
+            }
+        }
+        Some(report)
+    }
+
+    /// A table with a column for each toolchain.
+    pub fn to_table(&self) -> SupportTable {
+        let mut table = SupportTable::new();
+        for toolchain in &self.toolchains {
+            if let Some(report) = self.report(toolchain) {
+                table.column(toolchain.as_str(), &report);
+            }
+        }
+        table
+    }
+}
+
+impl Probe {
+    /// Runs each of the named probe programs with each of the rustup
+    /// toolchains, at most `jobs` compilations at a time, and collects every
+    /// outcome.
+    ///
+    /// The toolchains are first found one at a time, since that runs rustup,
+    /// as with [`toolchain`](#method.toolchain). The compilations then run
+    /// on worker threads in probe order, alternating between toolchains, so
+    /// that every toolchain makes progress. A `jobs` of zero counts as one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOutcome};
+    ///
+    /// let probe = Probe::new();
+    /// let matrix = probe.probe_matrix(&["stable", "no-such-toolchain"], &[
+    ///     ("u32",  "fn main() { let _: u32 = 0; }"),
+    ///     ("u512", "fn main() { let _: u512 = 0; }"),
+    /// ], 4);
+    ///
+    /// assert_eq!( matrix.get("stable", "u32"), Some(ProbeOutcome::Present) );
+    /// assert_eq!( matrix.get("stable", "u512"), Some(ProbeOutcome::Absent) );
+    /// assert_eq!( matrix.get("no-such-toolchain", "u32"), Some(ProbeOutcome::Unknown) );
+    /// ```
+    pub fn probe_matrix(&self, toolchains: &[&str], probes: &[(&str, &str)],
+                        jobs: usize) -> ProbeMatrix {
+        let resolved: Vec<Option<Probe>> = toolchains.iter()
+            .map(|toolchain| {
+                let mut probe = self.clone();
+                match probe.toolchain(toolchain) {
+                    Ok(_)  => Some(probe),
+                    Err(_) => None,
+                }
+            })
+            .collect();
+
+        let mut work = Vec::new();
+        for (column, &(name, code)) in probes.iter().enumerate() {
+            for (row, probe) in resolved.iter().enumerate() {
+                if let Some(ref probe) = *probe {
+                    work.push((row * probes.len() + column, probe.named(name), code.to_owned()));
+                }
+            }
+        }
+        work.reverse();
+
+        let outcomes = Arc::new(Mutex::new(vec![ProbeOutcome::Unknown;
+                                                toolchains.len() * probes.len()]));
+        let work = Arc::new(Mutex::new(work));
+        let workers: Vec<_> = (0..cmp::max(jobs, 1))
+            .map(|_| {
+                let work = work.clone();
+                let outcomes = outcomes.clone();
+                thread::spawn(move || loop {
+                    let item = work.lock().unwrap().pop();
+                    let (cell, probe, code) = match item {
+                        Some(item) => item,
+                        None       => return,
+                    };
+                    let outcome = outcome::from_result(&probe.probe_result(&code));
+                    outcomes.lock().unwrap()[cell] = outcome;
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            // A worker that panicked leaves its cell unknown.
+            let _ = worker.join();
+        }
+
+        let outcomes = outcomes.lock().unwrap().clone();
+        ProbeMatrix {
+            toolchains: toolchains.iter().map(|t| t.to_string()).collect(),
+            names:      probes.iter().map(|p| p.0.to_owned()).collect(),
+            outcomes:   outcomes,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::ffi::OsString;
+use std::io;
+
+use super::Probe;
+
+impl Probe {
+    /// Probes whether a program can be linked against the named native
+    /// library, as with `-l NAME`.
+    ///
+    /// Unlike other probes, this links a binary, so it needs a working
+    /// linker for the target. The binary is written to a temporary directory
+    /// and discarded.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( ! probe.probe_link("no_such_library_xyz") );
+    /// ```
+    pub fn probe_link(&self, library: &str) -> bool {
+        self.probe_link_with("fn main() { }", &["-l", library])
+    }
+
+    /// Probes whether the C function `symbol` with the given signature can
+    /// be linked against.
+    ///
+    /// The signature is the parameter list and return type as they would be
+    /// written in an `extern "C"` block, such as `"(buf: *mut u8, len: usize)
+    /// -> isize"`. It is not checked against the real function, which a
+    /// linker cannot do, but a malformed signature makes the probe fail.
+    /// Libraries the symbol lives in can be passed with
+    /// [`arg`](#method.arg), for example `probe.arg("-lssl")`.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_c_symbol("strlen", "(s: *const u8) -> usize") );
+    /// assert!( ! probe.probe_c_symbol("no_such_symbol_xyz", "()") );
+    /// ```
+    pub fn probe_c_symbol(&self, symbol: &str, signature: &str) -> bool {
+        // Starting with the 2024 edition, `extern` blocks must be `unsafe`.
+        let unsafe_extern = match self.edition {
+            Some(ref edition) if edition.as_str() >= "2024" => "unsafe ",
+            _                                               => "",
+        };
+
+        // Reading the address through a volatile load keeps the reference
+        // to the symbol from being optimized away before linking.
+        self.probe_link_with(&format!("{0}extern \"C\" {{ fn {1}{2}; }} \
+                                       fn main() {{ \
+                                           let address = {1} as usize; \
+                                           let address = unsafe {{ ::std::ptr::read_volatile(&address) }}; \
+                                           if address == 0 {{ ::std::process::exit(1) }} \
+                                       }}",
+                                      unsafe_extern, symbol, signature),
+                             &[] as &[&str])
+    }
+
+    /// Probes whether the given program can be compiled and linked into a
+    /// binary, with the given extra arguments to `rustc`, such as `-l` and
+    /// `-L` flags.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let code = "extern \"C\" { fn no_such_symbol_xyz(); } \
+    ///             fn main() { unsafe { no_such_symbol_xyz() } }";
+    /// assert!(   probe.probe_link_with("fn main() { }", &[] as &[&str]) );
+    /// assert!(   probe.probe(code) );
+    /// assert!( ! probe.probe_link_with(code, &[] as &[&str]) );
+    /// ```
+    pub fn probe_link_with<S>(&self, code: &str, args: &[S]) -> bool
+        where S: Clone + Into<OsString>
+    {
+        self.probe_link_result(code, args).expect("Probe::probe_link_with")
+    }
+
+    /// Probes whether the given program can be compiled and linked into a
+    /// binary, with the given extra arguments to `rustc`.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_link_result<S>(&self, code: &str, args: &[S]) -> io::Result<bool>
+        where S: Clone + Into<OsString>
+    {
+        let mut probe = self.clone();
+        probe.emit = String::from("link");
+        probe.crate_type = None;
+        probe.args(args.iter().cloned());
+        probe.probe_result_in_temp(code)
+    }
+}
@@ -0,0 +1,194 @@
+use std::ffi::OsString;
+use std::io;
+
+use super::{Probe, TypeHarness, NO_STD_STUBS};
+
+/// Settings that override a [`Probe`](struct.Probe.html)'s configuration for
+/// a single probe.
+///
+/// Pass these to [`Probe::probe_with`](struct.Probe.html#method.probe_with)
+/// and the other `_with` methods when one probe needs a different edition,
+/// target or flags from the rest, instead of cloning and reconfiguring the
+/// whole probe.
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::{Probe, ProbeOptions};
+///
+/// let probe = Probe::new();
+/// assert!( ! probe.probe_with("async fn f() { } fn main() { }", &ProbeOptions::new()) );
+/// assert!(   probe.probe_with("async fn f() { } fn main() { }",
+///                             ProbeOptions::new().edition("2018")) );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProbeOptions {
+    edition:       Option<String>,
+    target:        Option<OsString>,
+    deny_warnings: bool,
+    no_std:        bool,
+    args:          Vec<OsString>,
+    harness:       TypeHarness,
+}
+
+impl ProbeOptions {
+    /// Creates options that override nothing.
+    pub fn new() -> Self {
+        ProbeOptions::default()
+    }
+
+    /// Overrides the edition, as [`Probe::edition`](struct.Probe.html#method.edition)
+    /// does.
+    pub fn edition<S: Into<String>>(&mut self, edition: S) -> &mut Self {
+        self.edition = Some(edition.into());
+        self
+    }
+
+    /// Overrides the target, as [`Probe::target`](struct.Probe.html#method.target)
+    /// does.
+    pub fn target<S: Into<OsString>>(&mut self, target: S) -> &mut Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Makes warnings in the probe program errors, so that, for example,
+    /// using a deprecated item counts as absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOptions};
+    ///
+    /// let probe = Probe::new();
+    /// let code = "fn main() { let x = 1; }";
+    /// assert!(   probe.probe_with(code, &ProbeOptions::new()) );
+    /// assert!( ! probe.probe_with(code, ProbeOptions::new().deny_warnings(true)) );
+    /// ```
+    pub fn deny_warnings(&mut self, deny_warnings: bool) -> &mut Self {
+        self.deny_warnings = deny_warnings;
+        self
+    }
+
+    /// Compiles the probe program as a `#![no_std]` crate, supplying what a
+    /// `no_std` executable would otherwise need to compile, so that the
+    /// probe fails only if what it tests is missing.
+    ///
+    /// The program is compiled with `-C panic=abort`, so it needs no
+    /// `eh_personality`, and with `#![no_main]` and a `#[panic_handler]`
+    /// added, so it doesn't define either. Any `main` it does define is
+    /// ignored. Its own inner attributes, such as `#![feature(…)]`, must
+    /// still come first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOptions};
+    ///
+    /// let probe = Probe::new();
+    /// let code = "pub fn count(x: core::num::NonZeroU8) -> u32 { x.get().count_ones() }";
+    /// assert!( ! probe.probe_with(&format!("#![no_std] {}", code), &ProbeOptions::new()) );
+    /// assert!(   probe.probe_with(code, ProbeOptions::new().no_std(true)) );
+    /// ```
+    pub fn no_std(&mut self, no_std: bool) -> &mut Self {
+        self.no_std = no_std;
+        self
+    }
+
+    /// Adds an argument to pass to `rustc`, after the probe's own arguments.
+    pub fn arg<S: Into<OsString>>(&mut self, arg: S) -> &mut Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Sets how [`Probe::probe_type_with`](struct.Probe.html#method.probe_type_with)
+    /// mentions the type it probes for. Other probes ignore this.
+    pub fn harness(&mut self, harness: TypeHarness) -> &mut Self {
+        self.harness = harness;
+        self
+    }
+}
+
+impl Probe {
+    /// Probes for the given type by name, with the given options.
+    ///
+    /// Besides overriding this probe's configuration, the options choose how
+    /// the type is mentioned. [`probe_type`](#method.probe_type) only checks
+    /// that the type can be named; other harnesses check that it is well
+    /// formed or usable in a particular position. See
+    /// [`TypeHarness`](enum.TypeHarness.html).
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOptions, TypeHarness};
+    ///
+    /// let probe = Probe::new();
+    /// let phantom = ProbeOptions::new().harness(TypeHarness::PhantomData).clone();
+    /// let value   = ProbeOptions::new().harness(TypeHarness::Value).clone();
+    ///
+    /// assert!(   probe.probe_type_with("str", &phantom) );
+    /// assert!( ! probe.probe_type_with("str", &value) );
+    /// assert!(   probe.probe_type("Vec<str>") );
+    /// assert!( ! probe.probe_type_with("Vec<str>", &phantom) );
+    /// ```
+    pub fn probe_type_with(&self, type_name: &str, options: &ProbeOptions) -> bool {
+        self.probe_with(&options.harness.render(type_name), options)
+    }
+
+    /// Probes for whether a whole program can be compiled, with the given
+    /// options overriding this probe's configuration.
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOptions};
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.probe_with("fn main() { let r#try = 1; let _ = r#try; }",
+    ///                           ProbeOptions::new().edition("2018")) );
+    /// ```
+    pub fn probe_with(&self, code: &str, options: &ProbeOptions) -> bool {
+        self.probe_result_with(code, options).expect("Probe::probe_with")
+    }
+
+    /// Probes for whether a whole program can be compiled, with the given
+    /// options overriding this probe's configuration.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    pub fn probe_result_with(&self, code: &str, options: &ProbeOptions) -> io::Result<bool> {
+        if *options == ProbeOptions::default() {
+            return self.probe_result(code);
+        }
+
+        let mut probe = self.clone();
+        if let Some(ref edition) = options.edition {
+            probe.edition(edition.as_str());
+        }
+        if let Some(ref target) = options.target {
+            probe.target(target.as_os_str());
+        }
+        if options.deny_warnings {
+            probe.arg("-Dwarnings");
+        }
+        probe.args(&options.args);
+
+        if options.no_std {
+            probe.arg("-C").arg("panic=abort");
+            return probe.probe_result(&format!("#![no_std]\n#![no_main]\n{}\n{}",
+                                               code, NO_STD_STUBS));
+        }
+
+        probe.probe_result(code)
+    }
+}
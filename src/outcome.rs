@@ -0,0 +1,50 @@
+use std::io;
+
+/// The outcome of a probe: whether the feature is present, absent, or
+/// couldn't be determined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ProbeOutcome {
+    /// The probe program compiled.
+    Present,
+    /// The probe program did not compile.
+    Absent,
+    /// The probe could not be carried out, so the answer is unknown.
+    Unknown,
+}
+
+impl ProbeOutcome {
+    /// Whether the outcome is `Present`.
+    pub fn is_present(self) -> bool {
+        self == ProbeOutcome::Present
+    }
+
+    /// Whether the outcome is `Unknown`.
+    pub fn is_unknown(self) -> bool {
+        self == ProbeOutcome::Unknown
+    }
+
+    /// Converts the outcome to a `bool`, or `None` if it is `Unknown`.
+    pub fn to_option(self) -> Option<bool> {
+        match self {
+            ProbeOutcome::Present => Some(true),
+            ProbeOutcome::Absent  => Some(false),
+            ProbeOutcome::Unknown => None,
+        }
+    }
+}
+
+/// The outcome of a probe that gave the result.
+pub fn from_result(result: &io::Result<bool>) -> ProbeOutcome {
+    match *result {
+        Ok(true)  => ProbeOutcome::Present,
+        Ok(false) => ProbeOutcome::Absent,
+        Err(_)    => ProbeOutcome::Unknown,
+    }
+}
+
+impl From<bool> for ProbeOutcome {
+    fn from(present: bool) -> Self {
+        if present { ProbeOutcome::Present } else { ProbeOutcome::Absent }
+    }
+}
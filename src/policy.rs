@@ -0,0 +1,50 @@
+/// What to answer when a probe can't be carried out, such as when `rustc`
+/// cannot be started, runs past its timeout, or the target isn't installed.
+///
+/// Set this with
+/// [`Probe::on_probe_unavailable`](struct.Probe.html#method.on_probe_unavailable).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnavailablePolicy {
+    /// Fail: methods that return a `Result` return the error, and the rest
+    /// panic. This is the default.
+    Panic,
+    /// Answer that the feature is absent.
+    AssumeAbsent,
+    /// Answer that the feature is present.
+    AssumePresent,
+    /// Answer as the probe's [`cache_file`](struct.Probe.html#method.cache_file)
+    /// recorded when the same probe last succeeded, and fail as with
+    /// `Panic` if it has no answer.
+    FromCache,
+}
+
+impl Default for UnavailablePolicy {
+    fn default() -> Self {
+        UnavailablePolicy::Panic
+    }
+}
+
+/// What to do when probes would be compiled by a different toolchain than
+/// the crate being built.
+///
+/// Set this with
+/// [`Probe::on_toolchain_mismatch`](struct.Probe.html#method.on_toolchain_mismatch).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ToolchainPolicy {
+    /// Don't check.
+    Ignore,
+    /// Report a `cargo:warning` once, and probe anyway. This is the default.
+    Warn,
+    /// Fail every probe with the mismatch, whatever the
+    /// [`on_probe_unavailable`](struct.Probe.html#method.on_probe_unavailable)
+    /// policy.
+    Error,
+}
+
+impl Default for ToolchainPolicy {
+    fn default() -> Self {
+        ToolchainPolicy::Warn
+    }
+}
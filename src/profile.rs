@@ -0,0 +1,93 @@
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// The kind of compiler that probes are compiled with, which determines how
+/// it is invoked.
+///
+/// The profile is detected from the name of the compiler (see
+/// [`detect`](#method.detect)) and can be set with
+/// [`Probe::compiler_profile`](struct.Probe.html#method.compiler_profile).
+/// Support for compilers other than `rustc` is limited to probes that only
+/// check whether a program compiles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CompilerProfile {
+    /// `rustc`, or anything that accepts the same arguments.
+    Rustc,
+    /// Clippy's `clippy-driver`, which takes `rustc`'s arguments and also
+    /// runs Clippy's lints.
+    ClippyDriver,
+    /// The GCC front end for Rust, which takes GCC-style arguments and reads
+    /// programs from files.
+    Gccrs,
+    /// `mrustc`, which reads programs from files.
+    Mrustc,
+}
+
+impl CompilerProfile {
+    /// Guesses the profile from the name of the compiler executable,
+    /// defaulting to [`Rustc`](#variant.Rustc).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::CompilerProfile;
+    ///
+    /// assert_eq!( CompilerProfile::detect("/usr/bin/rustc"), CompilerProfile::Rustc );
+    /// assert_eq!( CompilerProfile::detect("clippy-driver"),  CompilerProfile::ClippyDriver );
+    /// assert_eq!( CompilerProfile::detect("gccrs-14"),       CompilerProfile::Gccrs );
+    /// ```
+    pub fn detect<P: AsRef<Path>>(compiler: P) -> Self {
+        let stem = compiler.as_ref().file_stem()
+            .and_then(OsStr::to_str)
+            .unwrap_or("");
+
+        if stem.starts_with("clippy-driver") {
+            CompilerProfile::ClippyDriver
+        } else if stem.starts_with("gccrs") {
+            CompilerProfile::Gccrs
+        } else if stem.starts_with("mrustc") {
+            CompilerProfile::Mrustc
+        } else {
+            CompilerProfile::Rustc
+        }
+    }
+
+    /// Whether the compiler accepts `rustc`'s arguments.
+    pub fn accepts_rustc_flags(self) -> bool {
+        match self {
+            CompilerProfile::Rustc | CompilerProfile::ClippyDriver => true,
+            CompilerProfile::Gccrs | CompilerProfile::Mrustc       => false,
+        }
+    }
+
+    /// Whether the compiler can read a program from standard input, given as
+    /// the file `-`. Programs for other compilers are written to a file.
+    pub fn reads_stdin(self) -> bool {
+        self.accepts_rustc_flags()
+    }
+
+    /// Whether the compiler can emit output of the given `--emit` kind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::CompilerProfile;
+    ///
+    /// assert!(   CompilerProfile::Rustc.supports_emit("llvm-ir") );
+    /// assert!( ! CompilerProfile::Gccrs.supports_emit("llvm-ir") );
+    /// ```
+    pub fn supports_emit(self, kind: &str) -> bool {
+        match self {
+            CompilerProfile::Rustc | CompilerProfile::ClippyDriver => true,
+            CompilerProfile::Gccrs  => kind == "obj",
+            CompilerProfile::Mrustc => kind == "link",
+        }
+    }
+}
+
+impl Default for CompilerProfile {
+    fn default() -> Self {
+        CompilerProfile::Rustc
+    }
+}
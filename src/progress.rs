@@ -0,0 +1,82 @@
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::Reporter;
+use stats::Seconds;
+
+/// Where a probe reports its progress, for build scripts that run enough
+/// probes to take a while.
+///
+/// Set this with [`Probe::progress`](struct.Probe.html#method.progress).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Progress {
+    /// Report nothing. This is the default.
+    Off,
+    /// Write a line to standard error as each probe starts and finishes.
+    /// Cargo shows these as they happen with `cargo build -vv`, so a probe
+    /// that hangs can be identified.
+    Stderr,
+    /// Report a `cargo:warning` as each probe finishes, which Cargo shows
+    /// once the build script is done.
+    CargoWarning,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress::Off
+    }
+}
+
+/// Progress shared by a probe and its clones.
+#[derive(Debug)]
+pub struct Tracker {
+    output: Progress,
+    state:  Mutex<(u64, Option<Instant>)>,
+}
+
+impl Tracker {
+    pub fn new(output: Progress) -> Self {
+        Tracker {
+            output: output,
+            state: Mutex::new((0, None)),
+        }
+    }
+
+    pub fn output(&self) -> Progress {
+        self.output
+    }
+
+    /// Counts a probe that is starting, and returns its number.
+    pub fn start(&self, name: &str) -> u64 {
+        let number = {
+            let mut state = self.state.lock().unwrap();
+            state.0 += 1;
+            if state.1.is_none() {
+                state.1 = Some(Instant::now());
+            }
+            state.0
+        };
+
+        if self.output == Progress::Stderr {
+            let _ = writeln!(io::stderr(), "feature-probe: [{}] probing {}...", number, name);
+        }
+
+        number
+    }
+
+    /// Reports how a probe finished.
+    pub fn finish(&self, reporter: &Reporter, number: u64, name: &str,
+                  answer: &str, elapsed: Duration) {
+        let total = self.state.lock().unwrap().1.map_or(elapsed, |start| start.elapsed());
+        let line = format!("[{}] {}: {} in {} ({} since the first probe)",
+                           number, name, answer, Seconds(elapsed), Seconds(total));
+
+        match self.output {
+            Progress::Off          => (),
+            Progress::Stderr       => { let _ = writeln!(io::stderr(), "feature-probe: {}", line); }
+            Progress::CargoWarning => reporter.warning(&format!("feature-probe: {}", line)),
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::slice;
+use std::time::Duration;
+
+use super::{Reporter, StdoutReporter, SupportTable, Version};
+#[cfg(feature = "stabilization-db")]
+use stabilization;
+use stats::Seconds;
+
+/// The results of a set of named probes.
+///
+/// Record results with [`record`](#method.record), and then hand them to the
+/// build in whatever form is most convenient: as `cfg`s with
+/// [`emit_cfgs`](#method.emit_cfgs), or as Rust source with
+/// [`write_rust_module`](#method.write_rust_module).
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::{Probe, ProbeReport};
+///
+/// let probe = Probe::new();
+/// let mut report = ProbeReport::new();
+///
+/// report.record("i128", probe.probe_type("i128"));
+/// report.record("u512", probe.probe_type("u512"));
+///
+/// assert_eq!( report.get("u512"), Some(false) );
+/// assert_eq!( report.get("f16x"), None );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProbeReport {
+    entries: Vec<(String, bool)>,
+    timings: Vec<(String, Duration)>,
+    since:   Vec<(String, Version)>,
+}
+
+impl ProbeReport {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        ProbeReport {
+            entries: Vec::new(),
+            timings: Vec::new(),
+            since:   Vec::new(),
+        }
+    }
+
+    /// Records the result of the named probe, replacing any earlier result
+    /// of the same name, and returns the result.
+    pub fn record<S: Into<String>>(&mut self, name: S, present: bool) -> bool {
+        let name = name.into();
+
+        match self.entries.iter().position(|entry| entry.0 == name) {
+            Some(index) => self.entries[index].1 = present,
+            None        => self.entries.push((name, present)),
+        }
+
+        present
+    }
+
+    /// Records the result of the named probe along with how long it took,
+    /// and returns the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use feature_probe::ProbeReport;
+    ///
+    /// let mut report = ProbeReport::new();
+    /// report.record_timed("i128", true, Duration::from_millis(120));
+    /// report.record("u512", false);
+    ///
+    /// assert_eq!( report.elapsed("i128"), Some(Duration::from_millis(120)) );
+    /// assert_eq!( report.elapsed("u512"), None );
+    /// assert_eq!( report.timing_summary(), "i128: 0.120s\n" );
+    /// ```
+    pub fn record_timed<S: Into<String>>(&mut self, name: S, present: bool,
+                                         elapsed: Duration) -> bool {
+        let name = name.into();
+
+        match self.timings.iter().position(|entry| entry.0 == name) {
+            Some(index) => self.timings[index].1 = elapsed,
+            None        => self.timings.push((name.clone(), elapsed)),
+        }
+
+        self.record(name, present)
+    }
+
+    /// Records the result of the named probe along with the Rust release
+    /// that the probed feature first appeared in, and returns the result.
+    ///
+    /// See [`minimum_rust_version`](#method.minimum_rust_version).
+    pub fn record_since<S: Into<String>>(&mut self, name: S, present: bool,
+                                         since: Version) -> bool {
+        let name = name.into();
+
+        match self.since.iter().position(|entry| entry.0 == name) {
+            Some(index) => self.since[index].1 = since,
+            None        => self.since.push((name.clone(), since)),
+        }
+
+        self.record(name, present)
+    }
+
+    /// Looks up the Rust release that the named probe's feature first
+    /// appeared in.
+    ///
+    /// This is the version given to [`record_since`](#method.record_since),
+    /// or with the `stabilization-db` feature, the version in its table for
+    /// probes named after a well-known std item or language feature.
+    pub fn since(&self, name: &str) -> Option<Version> {
+        self.since.iter()
+            .find(|entry| entry.0 == name)
+            .map(|entry| entry.1)
+            .or_else(|| stabilized_in(name))
+    }
+
+    /// Reports the oldest Rust release that has every feature whose probe
+    /// succeeded, which is the minimum supported Rust version implied by
+    /// relying on them, or `None` if no successful probe has a known
+    /// [`since`](#method.since) version.
+    ///
+    /// Compare it against the `rust-version` in Cargo.toml to check that the
+    /// declared minimum is still accurate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{ProbeReport, Version};
+    ///
+    /// let mut report = ProbeReport::new();
+    /// report.record_since("try_from", true, Version::new(1, 34, 0));
+    /// report.record_since("let_else", true, Version::new(1, 65, 0));
+    /// report.record_since("f16", false, Version::new(1, 99, 0));
+    /// report.record("unsized_locals", true);
+    ///
+    /// assert_eq!( report.minimum_rust_version(), Some(Version::new(1, 65, 0)) );
+    /// ```
+    pub fn minimum_rust_version(&self) -> Option<Version> {
+        self.iter()
+            .filter(|entry| entry.1)
+            .filter_map(|entry| self.since(entry.0))
+            .max()
+    }
+
+    /// Looks up how long the named probe took, if that was recorded.
+    pub fn elapsed(&self, name: &str) -> Option<Duration> {
+        self.timings.iter()
+            .find(|entry| entry.0 == name)
+            .map(|entry| entry.1)
+    }
+
+    /// Lists the recorded timings, slowest first, one `name: seconds` line
+    /// per probe.
+    pub fn timing_summary(&self) -> String {
+        let mut timings = self.timings.clone();
+        timings.sort_by(|a, b| a.1.cmp(&b.1).reverse());
+
+        let mut summary = String::new();
+        for (name, elapsed) in timings {
+            summary.push_str(&format!("{}: {}\n", name, Seconds(elapsed)));
+        }
+        summary
+    }
+
+    /// Looks up the result of the named probe.
+    pub fn get(&self, name: &str) -> Option<bool> {
+        self.entries.iter()
+            .find(|entry| entry.0 == name)
+            .map(|entry| entry.1)
+    }
+
+    /// The number of recorded results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no results have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the recorded results in the order they were first
+    /// recorded.
+    pub fn iter<'a>(&'a self) -> ReportIter<'a> {
+        ReportIter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Emits `cargo:rustc-cfg=NAME` to standard output for every probe that
+    /// succeeded.
+    pub fn emit_cfgs(&self) {
+        self.emit_cfgs_to(&StdoutReporter);
+    }
+
+    /// Reports `cargo:rustc-cfg=NAME` to the given reporter for every probe
+    /// that succeeded.
+    pub fn emit_cfgs_to(&self, reporter: &Reporter) {
+        for (name, present) in self.iter() {
+            if present {
+                reporter.cfg(name);
+            }
+        }
+    }
+
+    /// Exports every result to standard output as `links` metadata, so that
+    /// build scripts of dependent crates can reuse them instead of probing
+    /// again.
+    ///
+    /// Each probe `name` is emitted as the key `has_name` with the value `1`
+    /// or `0`, which dependents see as the environment variable
+    /// `DEP_LINKS_HAS_NAME`. Use
+    /// [`from_dependency_metadata`](#method.from_dependency_metadata) to read
+    /// them back.
+    pub fn emit_metadata(&self) {
+        self.emit_metadata_to(&StdoutReporter);
+    }
+
+    /// Reports every result to the given reporter as `links` metadata, in the
+    /// form described for [`emit_metadata`](#method.emit_metadata).
+    pub fn emit_metadata_to(&self, reporter: &Reporter) {
+        for (name, present) in self.iter() {
+            reporter.metadata(&format!("has_{}", identifier(name)),
+                              if present { "1" } else { "0" });
+        }
+    }
+
+    /// Reads the results exported by a dependency's
+    /// [`emit_metadata`](#method.emit_metadata), given the dependency's
+    /// `links` value.
+    ///
+    /// Since Cargo uppercases metadata keys, the names in the returned report
+    /// are lowercase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::ProbeReport;
+    ///
+    /// let report = ProbeReport::from_dependency_metadata("nonexistent");
+    /// assert!( report.is_empty() );
+    /// ```
+    pub fn from_dependency_metadata(links: &str) -> Self {
+        let prefix = format!("DEP_{}_HAS_", links.to_uppercase().replace('-', "_"));
+        let mut vars: Vec<_> = env::vars_os()
+            .filter_map(|(var, value)| var.into_string().ok().map(|var| (var, value)))
+            .filter(|entry| entry.0.starts_with(&prefix) && entry.0.len() > prefix.len())
+            .collect();
+        vars.sort();
+
+        let mut report = ProbeReport::new();
+
+        for (var, value) in vars {
+            report.record(var[prefix.len()..].to_lowercase(), value.to_str() == Some("1"));
+        }
+
+        report
+    }
+
+    /// Hands the results to GitHub Actions when running under it, and
+    /// returns whether it did.
+    ///
+    /// Each probe `name` is appended to the file named by `GITHUB_OUTPUT` as
+    /// the step output `has_name`, `true` or `false`, so that later steps and
+    /// jobs can branch on it. A table of the results is appended to the file
+    /// named by `GITHUB_STEP_SUMMARY`. Outside of Actions, when neither
+    /// variable is set, this does nothing.
+    ///
+    /// # Errors
+    ///
+    /// If either file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{env, fs};
+    /// use feature_probe::ProbeReport;
+    ///
+    /// let mut report = ProbeReport::new();
+    /// report.record("i128", true);
+    ///
+    /// let path = env::temp_dir().join("feature_probe_doc_github_output");
+    /// let _ = fs::remove_file(&path);
+    /// env::set_var("GITHUB_OUTPUT", &path);
+    /// env::remove_var("GITHUB_STEP_SUMMARY");
+    ///
+    /// assert!( report.write_github_outputs().unwrap() );
+    /// assert_eq!( fs::read_to_string(&path).unwrap(), "has_i128=true\n" );
+    /// ```
+    pub fn write_github_outputs(&self) -> io::Result<bool> {
+        let output = env::var_os("GITHUB_OUTPUT");
+        let summary = env::var_os("GITHUB_STEP_SUMMARY");
+
+        if let Some(ref path) = output {
+            let mut lines = String::new();
+            for (name, present) in self.iter() {
+                lines.push_str(&format!("has_{}={}\n", identifier(name), present));
+            }
+            append(path.as_ref(), &lines)?;
+        }
+
+        if let Some(ref path) = summary {
+            let table = SupportTable::new().column("Present", self).to_markdown();
+            append(path.as_ref(), &format!("### Feature probes\n\n{}\n", table))?;
+        }
+
+        Ok(output.is_some() || summary.is_some())
+    }
+
+    /// Writes the results as a Rust source file.
+    ///
+    /// For each probe `name` the file contains a constant `HAS_NAME` and a
+    /// macro `if_name!` that expands to its input when the probe succeeded
+    /// and to nothing otherwise. Characters that cannot appear in identifiers
+    /// are replaced by underscores. Write the file into `OUT_DIR` and include
+    /// it from the crate with
+    /// `include!(concat!(env!("OUT_DIR"), "/probes.rs"));`.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be written, or with kind `InvalidInput` if two
+    /// probe names give the same constant, such as `a-b` and `a_b`, since the
+    /// module wouldn't compile. Nothing is written then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::ProbeReport;
+    ///
+    /// let mut report = ProbeReport::new();
+    /// report.record("i128", true);
+    ///
+    /// let path = std::env::temp_dir().join("feature_probe_doc_probes.rs");
+    /// report.write_rust_module(&path).unwrap();
+    ///
+    /// report.record("a-b", true);
+    /// report.record("a_b", false);
+    /// assert!( report.write_rust_module(&path).is_err() );
+    /// ```
+    pub fn write_rust_module<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let module = self.to_rust_module()?;
+        let mut file = File::create(path)?;
+        file.write_all(module.as_bytes())
+    }
+
+    fn to_rust_module(&self) -> io::Result<String> {
+        let mut out = String::from("// Generated by feature-probe. Do not edit.\n");
+        let mut seen = BTreeMap::new();
+
+        for (name, present) in self.iter() {
+            let ident = identifier(name);
+            if let Some(other) = seen.insert(ident.to_uppercase(), name) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          format!("probes `{}` and `{}` both give HAS_{}",
+                                                  other, name, ident.to_uppercase())));
+            }
+            out.push_str(&format!("\npub const HAS_{}: bool = {};\n",
+                                  ident.to_uppercase(), present));
+            out.push_str(&format!("#[allow(unused_macros)]\nmacro_rules! if_{} {{\n",
+                                  ident));
+            if present {
+                out.push_str("    ($($t:tt)*) => { $($t)* };\n}\n");
+            } else {
+                out.push_str("    ($($t:tt)*) => { };\n}\n");
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+impl<'a> IntoIterator for &'a ProbeReport {
+    type Item     = (&'a str, bool);
+    type IntoIter = ReportIter<'a>;
+
+    fn into_iter(self) -> ReportIter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the results in a [`ProbeReport`](struct.ProbeReport.html).
+#[derive(Clone, Debug)]
+pub struct ReportIter<'a> {
+    inner: slice::Iter<'a, (String, bool)>,
+}
+
+impl<'a> Iterator for ReportIter<'a> {
+    type Item = (&'a str, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (entry.0.as_str(), entry.1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(feature = "stabilization-db")]
+fn stabilized_in(name: &str) -> Option<Version> {
+    stabilization::lookup(name)
+}
+
+#[cfg(not(feature = "stabilization-db"))]
+fn stabilized_in(_name: &str) -> Option<Version> {
+    None
+}
+
+fn append(path: &Path, text: &str) -> io::Result<()> {
+    OpenOptions::new().create(true).append(true).open(path)?
+        .write_all(text.as_bytes())
+}
+
+fn identifier(name: &str) -> String {
+    name.chars()
+        // Radix 36 digits are the ASCII letters and digits.
+        .map(|c| if c.is_digit(36) || c == '_' { c } else { '_' })
+        .collect()
+}
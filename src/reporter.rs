@@ -0,0 +1,261 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+#[allow(deprecated)]
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::sync::{Arc, Mutex};
+
+use super::{cfg_string, Version};
+
+/// A destination for the `cargo:` directives that a build script emits.
+///
+/// A [`Probe`](struct.Probe.html) sends its directives to its reporter, which
+/// by default is [`StdoutReporter`](struct.StdoutReporter.html). Substituting
+/// [`RecordingReporter`](struct.RecordingReporter.html) with
+/// [`Probe::reporter`](struct.Probe.html#method.reporter) lets tests capture
+/// exactly what a build script would emit, and other build systems can
+/// implement this trait to collect directives programmatically.
+pub trait Reporter: Send + Sync {
+    /// Reports the directive `cargo:KEY=VALUE`.
+    fn directive(&self, key: &str, value: &str);
+
+    /// Reports `cargo:rustc-cfg=NAME`.
+    fn cfg(&self, name: &str) {
+        self.directive("rustc-cfg", name);
+    }
+
+    /// Reports `cargo:KEY=VALUE` metadata for dependents of a `links` crate.
+    ///
+    /// # Panics
+    ///
+    /// If the key contains `=` or `:` or either argument contains a newline,
+    /// since that would corrupt the directive, or if the key is one that
+    /// Cargo reserves for its own directives, such as `rustc-cfg` or
+    /// `warning`, since the metadata would then be taken as that directive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{RecordingReporter, Reporter};
+    ///
+    /// let recorder = RecordingReporter::new();
+    /// recorder.metadata("has_i128", "1");
+    /// assert_eq!( recorder.directives(), vec!["cargo:has_i128=1".to_owned()] );
+    /// ```
+    ///
+    /// ```should_panic
+    /// use feature_probe::{RecordingReporter, Reporter};
+    ///
+    /// RecordingReporter::new().metadata("rustc-link-lib", "ssl");
+    /// ```
+    fn metadata(&self, key: &str, value: &str) {
+        check_metadata(key, value);
+        self.directive(key, value);
+    }
+
+    /// Reports `cargo:rustc-cfg=NAME="VALUE"`, escaping the value as a Rust
+    /// string literal.
+    fn cfg_value(&self, name: &str, value: &str) {
+        self.cfg(&format!("{}={}", name, cfg_string(value)));
+    }
+
+    /// Reports `cargo:rustc-check-cfg=cfg(NAME, values(…))`, declaring the
+    /// expected values of a configuration option, or with no values,
+    /// declaring an option that has none, as `cargo:rustc-check-cfg=cfg(NAME)`.
+    fn check_cfg(&self, name: &str, values: &[&str]) {
+        if values.is_empty() {
+            self.directive("rustc-check-cfg", &format!("cfg({})", name));
+        } else {
+            let values: Vec<String> = values.iter().map(|value| cfg_string(value)).collect();
+            self.directive("rustc-check-cfg",
+                           &format!("cfg({}, values({}))", name, values.join(", ")));
+        }
+    }
+
+    /// Reports `cargo:rerun-if-changed=PATH`.
+    fn rerun_if_changed(&self, path: &Path) {
+        self.directive("rerun-if-changed", &path.display().to_string());
+    }
+
+    /// Reports `cargo:rerun-if-env-changed=VAR`.
+    fn rerun_if_env_changed(&self, var: &str) {
+        self.directive("rerun-if-env-changed", var);
+    }
+
+    /// Reports `cargo:warning=MESSAGE`, which Cargo shows once the build
+    /// script is done, with a directive for each line of the message.
+    fn warning(&self, message: &str) {
+        for line in message.lines() {
+            self.directive("warning", line);
+        }
+    }
+
+    /// Reports an error, which should fail the build once the build script
+    /// is done.
+    ///
+    /// Only Cargo 1.84 and later understand `cargo::error`, so by default
+    /// this reports each line as a warning starting with `error:`, and the
+    /// caller should still fail if the build must not go on.
+    /// [`StdoutReporter`](struct.StdoutReporter.html) uses `cargo::error`
+    /// where Cargo understands it.
+    fn error(&self, message: &str) {
+        for line in message.lines() {
+            self.directive("warning", &format!("error: {}", line));
+        }
+    }
+}
+
+/// Keys that Cargo takes as its own directives rather than as metadata,
+/// besides those starting with `rustc-` or `rerun-if-`.
+const RESERVED_KEYS: &'static [&'static str] = &["warning", "error", "metadata"];
+
+/// Panics if the metadata would corrupt its directive or be taken as
+/// another directive.
+fn check_metadata(key: &str, value: &str) {
+    assert!(!key.contains('=') && !key.contains(':') && !key.contains('\n')
+                && !value.contains('\n'),
+            "Reporter::metadata: invalid key or value");
+    assert!(!key.starts_with("rustc-") && !key.starts_with("rerun-if-")
+                && !RESERVED_KEYS.contains(&key),
+            "Reporter::metadata: key {:?} is reserved by Cargo", key);
+}
+
+/// The first Cargo that understands `cargo::error`.
+const CARGO_ERROR_VERSION: Version = Version { major: 1, minor: 84, patch: 0 };
+
+/// The first Cargo that accepts the `cargo::` syntax, which it refuses in
+/// packages whose `rust-version` is older.
+const CARGO_SYNTAX_VERSION: Version = Version { major: 1, minor: 77, patch: 0 };
+
+// What the Cargo running the build script accepts from the package being
+// built, as `cargo_support` finds.
+const OLD_SYNTAX_ONLY: usize = 1;
+const NEW_SYNTAX: usize = 2;
+const NEW_SYNTAX_AND_ERROR: usize = 3;
+
+/// Whether the Cargo running the build script accepts the `cargo::` syntax
+/// from the package being built, and if so, whether it understands
+/// `cargo::error`. Cargo is asked once per process.
+fn cargo_support() -> usize {
+    // `AtomicUsize::new` would require Rust 1.24.
+    #[allow(deprecated)]
+    static SUPPORT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    let known = SUPPORT.load(Ordering::Relaxed);
+    if known != 0 {
+        return known;
+    }
+
+    let msrv_allows = env::var("CARGO_PKG_RUST_VERSION").ok()
+        .and_then(|msrv| Version::parse(&msrv))
+        .map_or(true, |msrv| msrv >= CARGO_SYNTAX_VERSION);
+    let cargo = if msrv_allows {
+        env::var_os("CARGO")
+            .and_then(|cargo| Command::new(cargo).arg("-V").output().ok())
+            .and_then(|output| String::from_utf8_lossy(&output.stdout)
+                      .split_whitespace().nth(1).and_then(Version::parse))
+    } else {
+        None
+    };
+    let support = match cargo {
+        Some(version) if version >= CARGO_ERROR_VERSION  => NEW_SYNTAX_AND_ERROR,
+        Some(version) if version >= CARGO_SYNTAX_VERSION => NEW_SYNTAX,
+        _                                                => OLD_SYNTAX_ONLY,
+    };
+
+    SUPPORT.store(support, Ordering::Relaxed);
+    support
+}
+
+/// The default reporter, which prints directives to standard output for
+/// Cargo to read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StdoutReporter;
+
+impl Reporter for StdoutReporter {
+    fn directive(&self, key: &str, value: &str) {
+        println!("cargo:{}={}", key, value);
+    }
+
+    /// Prints `cargo::metadata=KEY=VALUE` where Cargo accepts it, and
+    /// `cargo:KEY=VALUE` elsewhere; dependents read either the same way.
+    fn metadata(&self, key: &str, value: &str) {
+        check_metadata(key, value);
+        if cargo_support() >= NEW_SYNTAX {
+            println!("cargo::metadata={}={}", key, value);
+        } else {
+            self.directive(key, value);
+        }
+    }
+
+    fn error(&self, message: &str) {
+        let supported = cargo_support() == NEW_SYNTAX_AND_ERROR;
+        for line in message.lines() {
+            if supported {
+                println!("cargo::error={}", line);
+            } else {
+                println!("cargo:warning=error: {}", line);
+            }
+        }
+    }
+}
+
+/// A reporter that records directives in memory instead of printing them.
+///
+/// Clones share the same record, so keep a clone to inspect after handing
+/// the original to a [`Probe`](struct.Probe.html).
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::{Probe, RecordingReporter, ScriptedBackend};
+///
+/// let recorder = RecordingReporter::new();
+///
+/// let mut probe = Probe::new();
+/// probe.backend(ScriptedBackend::new().default_answer(false))
+///      .reporter(recorder.clone());
+/// probe.emit_cfg("int_128");
+///
+/// assert_eq!( recorder.cfgs(), vec!["int_128".to_owned()] );
+/// assert_eq!( recorder.directives(), vec!["cargo:rustc-cfg=int_128".to_owned()] );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RecordingReporter {
+    directives: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl RecordingReporter {
+    /// Creates a reporter with an empty record.
+    pub fn new() -> Self {
+        RecordingReporter {
+            directives: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// The directives reported so far, formatted as `cargo:KEY=VALUE` lines.
+    pub fn directives(&self) -> Vec<String> {
+        self.directives.lock().unwrap().iter()
+            .map(|entry| format!("cargo:{}={}", entry.0, entry.1))
+            .collect()
+    }
+
+    /// The names of the `cfg`s reported so far.
+    pub fn cfgs(&self) -> Vec<String> {
+        self.directives.lock().unwrap().iter()
+            .filter(|entry| entry.0 == "rustc-cfg")
+            .map(|entry| entry.1.clone())
+            .collect()
+    }
+
+    /// Forgets all directives reported so far.
+    pub fn clear(&self) {
+        self.directives.lock().unwrap().clear();
+    }
+}
+
+impl Reporter for RecordingReporter {
+    fn directive(&self, key: &str, value: &str) {
+        self.directives.lock().unwrap().push((key.to_owned(), value.to_owned()));
+    }
+}
@@ -0,0 +1,106 @@
+use std::io;
+
+use super::{Probe, TypeHarness, Version};
+
+impl Probe {
+    /// Fails the build with the given message unless the whole program
+    /// compiles.
+    ///
+    /// The message should tell the user what to do, such as which Rust
+    /// release to install. It is followed by what was probed and, if the
+    /// probe couldn't be carried out, why.
+    ///
+    /// # Panics
+    ///
+    /// If the program doesn't compile, or the child `rustc` cannot be
+    /// started or communicated with, with the message.
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// probe.require("fn main() { }", "this crate needs a working Rust compiler");
+    /// probe.require("fn main() { let _: u512 = 0; }",
+    ///               "this crate needs 512-bit integers; please wait for them");
+    /// ```
+    pub fn require(&self, code: &str, message: &str) {
+        self.require_probe(self.probe_result(code), "the probe program", message);
+    }
+
+    /// Fails the build with the given message unless the type can be named,
+    /// as [`probe_type`](#method.probe_type) checks.
+    ///
+    /// # Panics
+    ///
+    /// As for [`require`](#method.require).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// probe.require_type("i128", "this crate needs 128-bit integers; please use Rust 1.26+");
+    /// ```
+    pub fn require_type(&self, type_name: &str, message: &str) {
+        self.require_probe(self.probe_result(&TypeHarness::Alias.render(type_name)),
+                           &format!("type `{}`", type_name), message);
+    }
+
+    /// Fails the build with the given message unless the expression
+    /// compiles, as [`probe_expression`](#method.probe_expression) checks.
+    ///
+    /// # Panics
+    ///
+    /// As for [`require`](#method.require).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// probe.require_expression("Some(1).filter(|&n| n > 0)",
+    ///                          "this crate needs Option::filter; please use Rust 1.27+");
+    /// ```
+    pub fn require_expression(&self, expression: &str, message: &str) {
+        self.require_probe(self.probe_result(&format!("fn main() {{ {}; }}", expression)),
+                           &format!("expression `{}`", expression), message);
+    }
+
+    /// Fails the build with the given message unless the compiler is at
+    /// least the given version.
+    ///
+    /// # Panics
+    ///
+    /// As for [`require`](#method.require), or if `rustc -vV` cannot be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, Version};
+    ///
+    /// let probe = Probe::new();
+    /// probe.require_rustc_version(Version::new(1, 34, 0),
+    ///                             "this crate needs Rust 1.34 or newer");
+    /// ```
+    pub fn require_rustc_version(&self, version: Version, message: &str) {
+        let result = self.rustc_version().map(|found| found >= version);
+        self.require_probe(result, &format!("rustc {}", version), message);
+    }
+
+    /// Fails the build with the message unless the probe succeeded.
+    fn require_probe(&self, result: io::Result<bool>, what: &str, message: &str) {
+        let detail = match result {
+            Ok(true)  => return,
+            Ok(false) => match self.rustc_version() {
+                Ok(version) => format!("{} is not available with rustc {}", what, version),
+                Err(_)      => format!("{} is not available", what),
+            },
+            Err(e)    => format!("could not probe for {}: {}", what, e),
+        };
+        panic!("{}\n(feature-probe: {})", message, detail);
+    }
+}
@@ -0,0 +1,217 @@
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Read};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use super::{backend, outcome, spawn, temp, Probe, ProbeOutcome};
+
+/// What happened when a probe program was compiled and run.
+///
+/// See [`Probe::probe_run`](struct.Probe.html#method.probe_run).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RunOutcome {
+    /// The program did not compile.
+    CompileFailed,
+    /// The program compiled and ran.
+    Exited {
+        /// The exit code, or `None` if the program was terminated by a
+        /// signal.
+        code:   Option<i32>,
+        /// What the program wrote to standard output, with any invalid UTF-8
+        /// replaced.
+        stdout: String,
+    },
+}
+
+impl RunOutcome {
+    /// Whether the program compiled, ran and exited successfully.
+    pub fn success(&self) -> bool {
+        match *self {
+            RunOutcome::Exited { code: Some(0), .. } => true,
+            _                                        => false,
+        }
+    }
+
+    /// What the program wrote to standard output, if it ran.
+    pub fn stdout(&self) -> Option<&str> {
+        match *self {
+            RunOutcome::CompileFailed             => None,
+            RunOutcome::Exited { ref stdout, .. } => Some(stdout),
+        }
+    }
+}
+
+impl Probe {
+    /// Sets a command to run probe programs with, such as an emulator for a
+    /// cross target, as Cargo's `target.<triple>.runner` setting does.
+    ///
+    /// The first word is the program; the rest are arguments, which come
+    /// before the path of the probe binary. Without a runner, programs built
+    /// for another target can't be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.target("wasm32-wasip1").runner(&["wasmtime", "run"]);
+    /// ```
+    pub fn runner<I>(&mut self, runner: I) -> &mut Self
+        where I: IntoIterator,
+              I::Item: Into<OsString>
+    {
+        self.runner = Some(runner.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the runner from the `CARGO_TARGET_<TRIPLE>_RUNNER` environment
+    /// variable for the probe's target (or the host), if it is set, splitting
+    /// it on whitespace.
+    ///
+    /// Cargo doesn't pass runners from its configuration files to build
+    /// scripts, so this only finds runners set in the environment.
+    ///
+    /// # Errors
+    ///
+    /// If the host cannot be determined; see [`host`](#method.host).
+    pub fn runner_from_env(&mut self) -> io::Result<&mut Self> {
+        let triple = match self.target {
+            Some(ref target) => target.to_string_lossy().into_owned(),
+            None             => self.host()?,
+        };
+        let var = format!("CARGO_TARGET_{}_RUNNER",
+                          triple.to_uppercase().replace('-', "_").replace('.', "_"));
+
+        if let Ok(runner) = env::var(var) {
+            let words: Vec<_> = runner.split_whitespace().map(str::to_owned).collect();
+            if !words.is_empty() {
+                self.runner(words);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Compiles the given program into a binary and runs it, capturing its
+    /// exit code and standard output.
+    ///
+    /// Some questions, such as what the build machine's CPU supports at run
+    /// time, can only be answered by running code. The binary is built and
+    /// run in a temporary directory, which is removed afterwards. The
+    /// [`timeout`](#method.timeout), if set, applies to compiling and running
+    /// separately.
+    ///
+    /// The program is run with the [`runner`](#method.runner), if one is
+    /// set.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc` or the program cannot be started or communicated with,
+    /// if either runs past the timeout, or if probes are compiled for a
+    /// target other than the host (see
+    /// [`is_cross_compiling`](#method.is_cross_compiling)) and no runner is
+    /// set, since then the program can't run here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, RunOutcome};
+    ///
+    /// let probe = Probe::new();
+    ///
+    /// let outcome = probe.probe_run("fn main() { print!(\"{}\", std::mem::size_of::<usize>()) }")
+    ///                    .unwrap();
+    /// assert!( outcome.success() );
+    /// assert_eq!( outcome.stdout().unwrap().parse::<usize>().unwrap(),
+    ///             std::mem::size_of::<usize>() );
+    ///
+    /// assert_eq!( probe.probe_run("fn main() { std::process::exit(3) }").unwrap(),
+    ///             RunOutcome::Exited { code: Some(3), stdout: String::new() } );
+    /// assert_eq!( probe.probe_run("fn main() { oops }").unwrap(),
+    ///             RunOutcome::CompileFailed );
+    /// ```
+    pub fn probe_run(&self, code: &str) -> io::Result<RunOutcome> {
+        if self.runner.is_none() && self.is_cross_compiling()? {
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      "cannot run probe programs built for another target \
+                                       without a runner"));
+        }
+
+        if !self.profile().accepts_rustc_flags() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("cannot run probe programs built with {:?}",
+                                              self.profile())));
+        }
+
+        let dir    = temp::TempDir::new()?;
+        let binary = dir.path().join(format!("probe{}", env::consts::EXE_SUFFIX));
+
+        let mut probe = self.compiling_afresh();
+        probe.emit = String::from("link");
+        probe.crate_type = None;
+        probe.arg("-o").arg(&binary);
+
+        if !probe.compile(probe.command_in(Some(dir.path())), code)? {
+            return Ok(RunOutcome::CompileFailed);
+        }
+
+        let mut command = match self.runner {
+            Some(ref runner) if !runner.is_empty() => {
+                let mut command = spawn::program(&runner[0]);
+                command.args(&runner[1..]).arg(&binary);
+                command
+            }
+            _ => Command::new(&binary),
+        };
+
+        let mut child = command
+            .current_dir(dir.path())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        // Read on another thread so that a chatty program can't fill the
+        // pipe and block while we wait for it.
+        let mut pipe = child.stdout.take().unwrap();
+        let reader = thread::spawn(move || {
+            let mut bytes = Vec::new();
+            pipe.read_to_end(&mut bytes).map(|_| bytes)
+        });
+
+        let status = backend::wait_timeout(&mut child, self.timeout, self.cancel.as_ref(),
+                                           "probe program")?;
+        let bytes  = reader.join()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "reading probe output failed"))??;
+
+        Ok(RunOutcome::Exited {
+            code:   status.code(),
+            stdout: String::from_utf8_lossy(&bytes).into_owned(),
+        })
+    }
+    /// Probes whether the given program compiles, runs and exits
+    /// successfully.
+    ///
+    /// The outcome is [`Unknown`](enum.ProbeOutcome.html) when
+    /// [`probe_run`](#method.probe_run) would return an error, such as when
+    /// probes are compiled for another target and no
+    /// [`runner`](#method.runner) is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeOutcome};
+    ///
+    /// let mut probe = Probe::new();
+    /// assert_eq!( probe.probe_run_outcome("fn main() { }"), ProbeOutcome::Present );
+    /// assert_eq!( probe.probe_run_outcome("fn main() { panic!() }"), ProbeOutcome::Absent );
+    ///
+    /// probe.target("thumbv7em-none-eabihf");
+    /// assert_eq!( probe.probe_run_outcome("fn main() { }"), ProbeOutcome::Unknown );
+    /// ```
+    pub fn probe_run_outcome(&self, code: &str) -> ProbeOutcome {
+        outcome::from_result(&self.probe_run(code).map(|outcome| outcome.success()))
+    }
+}
@@ -0,0 +1,109 @@
+use std::io;
+
+use super::{shared, Probe};
+use super::shared::Namespace;
+
+/// A program every compiler accepts.
+const VALID: &'static str = "fn main() { }";
+
+/// A program every compiler rejects.
+const INVALID: &'static str = "fn main() { let _: () = 0; } // feature-probe self-check";
+
+/// The key under which a failed self-check is marked as reported.
+const REPORTED: &'static str = "// feature-probe self-check reported";
+
+impl Probe {
+    /// Checks that the compiler, as configured, actually decides whether
+    /// programs compile, by compiling a program that must compile and one
+    /// that must not.
+    ///
+    /// This catches wrappers, such as caching or distributed-compilation
+    /// shims, that report success without compiling or fail for every
+    /// input, which would otherwise make every probe give the same answer.
+    /// Each configuration (see [`fingerprint`](#method.fingerprint)) is
+    /// checked once per process, and later checks reuse the answers.
+    ///
+    /// # Errors
+    ///
+    /// With kind `InvalidData` if the compiler answers both programs the
+    /// same way, or if `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// assert!( Probe::new().self_check().is_ok() );
+    ///
+    /// let mut broken = Probe::new();
+    /// broken.wrapper_command(vec!["true"]);
+    /// let error = broken.self_check().unwrap_err();
+    /// assert!( error.to_string().contains("accepts a program it must reject") );
+    /// ```
+    pub fn self_check(&self) -> io::Result<()> {
+        let fingerprint = self.fingerprint()?;
+        let valid   = self.self_check_answer(fingerprint, VALID)?;
+        let invalid = self.self_check_answer(fingerprint, INVALID)?;
+
+        let problem = match (valid, invalid) {
+            (true, false) => return Ok(()),
+            (true, true)  => "accepts a program it must reject, \
+                              so every probe would report the feature present",
+            (false, _)    => "rejects a program it must accept, \
+                              so every probe would report the feature absent",
+        };
+
+        let wrapper = if self.wrapper.is_empty() {
+            String::new()
+        } else {
+            format!(" run through {:?}", self.wrapper)
+        };
+        Err(io::Error::new(io::ErrorKind::InvalidData,
+                           format!("compiler {:?}{} {}; check RUSTC_WRAPPER and \
+                                    other compiler wrappers",
+                                   self.rustc, wrapper, problem)))
+    }
+
+    /// Turns on or off running [`self_check`](#method.self_check) before the
+    /// first compilation of each configuration, failing every probe with its
+    /// error, whatever the
+    /// [`on_probe_unavailable`](#method.on_probe_unavailable) policy, if the
+    /// compiler misbehaves. The problem is also reported once as a build
+    /// error, with [`emit_error`](#method.emit_error). This is off by
+    /// default, since it costs two compilations.
+    pub fn auto_self_check(&mut self, check: bool) -> &mut Self {
+        self.auto_check = check;
+        self
+    }
+
+    /// Compiles one of the self-check programs, or reuses its answer.
+    fn self_check_answer(&self, fingerprint: u64, code: &str) -> io::Result<bool> {
+        if let Some(present) = shared::get(Namespace::Internal, fingerprint, code) {
+            return Ok(present);
+        }
+
+        let mut command = String::new();
+        let mut diagnostics = String::new();
+        let present = self.compile_capturing(code, &mut command, &mut diagnostics)?;
+        shared::insert(Namespace::Internal, fingerprint, code, present);
+        Ok(present)
+    }
+}
+
+/// Runs the self-check for `Probe::auto_self_check`, reporting a
+/// misbehaving compiler as a build error once per configuration, in case the
+/// caller ignores the failed probes.
+pub fn guard(probe: &Probe) -> io::Result<()> {
+    let result = probe.self_check();
+    if let Err(ref e) = result {
+        if e.kind() == io::ErrorKind::InvalidData {
+            if let Ok(fingerprint) = probe.fingerprint() {
+                if shared::get(Namespace::Internal, fingerprint, REPORTED).is_none() {
+                    shared::insert(Namespace::Internal, fingerprint, REPORTED, true);
+                    probe.emit_error(&format!("feature-probe: {}", e));
+                }
+            }
+        }
+    }
+    result
+}
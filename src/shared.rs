@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, Once};
+#[allow(deprecated)]
+use std::sync::ONCE_INIT;
+
+/// Answers shared by every probe in the process that opts in, keyed by the
+/// probe's fingerprint and program. The library's own bookkeeping is kept
+/// apart from probe answers, so that a probe program that happens to match
+/// one of its keys isn't answered from it.
+type Answers = Mutex<BTreeMap<(Namespace, u64, String), bool>>;
+
+/// Which kind of entry a key names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Namespace {
+    /// The answer to a probe program.
+    Probe,
+    /// A self-check or toolchain-check result, or a reported problem.
+    Internal,
+}
+
+fn answers() -> &'static Answers {
+    // `Once::new` would require Rust 1.32.
+    #[allow(deprecated)]
+    static INIT: Once = ONCE_INIT;
+    static mut ANSWERS: *const Answers = 0 as *const Answers;
+
+    // Safety: `ANSWERS` is written once, under `INIT`, before any read, and
+    // the map it points to is never freed.
+    unsafe {
+        INIT.call_once(|| {
+            ANSWERS = Box::into_raw(Box::new(Mutex::new(BTreeMap::new())));
+        });
+        &*ANSWERS
+    }
+}
+
+/// Looks up a shared answer.
+pub fn get(namespace: Namespace, fingerprint: u64, code: &str) -> Option<bool> {
+    answers().lock().unwrap().get(&(namespace, fingerprint, code.to_owned())).cloned()
+}
+
+/// Records a shared answer.
+pub fn insert(namespace: Namespace, fingerprint: u64, code: &str, present: bool) {
+    answers().lock().unwrap().insert((namespace, fingerprint, code.to_owned()), present);
+}
@@ -0,0 +1,109 @@
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// A command that runs the given program, which on Windows may be a batch
+/// file.
+///
+/// Windows runs `.bat` and `.cmd` files only through `cmd.exe`, which has
+/// quoting rules of its own. `Command` starts them itself, and since Rust
+/// 1.77 quotes their arguments for `cmd` and refuses arguments it can't pass
+/// safely, so batch files aren't wrapped here. Build scripts compiled with
+/// an older toolchain pass such arguments with only the usual quoting, so
+/// `&`, `|`, `%` and `"` in them may be interpreted by `cmd`.
+///
+/// # Examples
+///
+/// A `.cmd` wrapper receives an output directory containing a space and an
+/// `&` intact:
+///
+/// ```
+/// # #[cfg(windows)] fn main() {
+/// use std::{env, fs};
+/// use feature_probe::Probe;
+///
+/// let dir = env::temp_dir().join(format!("fp doc & wrapper {}", std::process::id()));
+/// fs::create_dir_all(&dir).unwrap();
+/// let wrapper = dir.join("rustc.cmd");
+/// fs::write(&wrapper, "@rustc %*\r\n").unwrap();
+///
+/// env::set_var("RUSTC", &wrapper);
+/// env::set_var("OUT_DIR", &dir);
+/// let probe = Probe::new();
+/// assert!( probe.probe_expression("0") );
+/// # }
+/// # #[cfg(not(windows))] fn main() { }
+/// ```
+pub fn program<S: AsRef<OsStr>>(program: S) -> Command {
+    Command::new(program)
+}
+
+/// The path to pass to a compiler, which on Windows is in verbatim (`\\?\`)
+/// form if it's absolute and too long for the usual `MAX_PATH` limit, as
+/// Cargo's deeply nested output directories can be.
+///
+/// Windows doesn't normalize verbatim paths, so `.` and `..` components
+/// and repeated separators are resolved first, as Windows would resolve
+/// them in an ordinary path.
+///
+/// # Examples
+///
+/// An output directory of more than 260 characters containing `..`:
+///
+/// ```
+/// # #[cfg(windows)] fn main() {
+/// use std::{env, fs};
+/// use feature_probe::Probe;
+///
+/// let mut dir = env::temp_dir().join(format!("fp-doc-long-path-{}", std::process::id()));
+/// for _ in 0 .. 10 {
+///     dir.push("a-directory-name-of-some-length");
+/// }
+/// fs::create_dir_all(&dir).unwrap();
+/// let out_dir = dir.join("child").join("..");
+/// assert!( out_dir.to_str().unwrap().len() > 260 );
+///
+/// env::set_var("OUT_DIR", &out_dir);
+/// let probe = Probe::new();
+/// assert!( probe.probe_expression("0") );
+/// # }
+/// # #[cfg(not(windows))] fn main() { }
+/// ```
+pub fn long_path(path: &Path) -> PathBuf {
+    // Leave room for the names of files created in the directory.
+    const LIMIT: usize = 200;
+
+    if !cfg!(windows) || !path.is_absolute() {
+        return path.to_owned();
+    }
+    match path.to_str() {
+        Some(text) if text.len() >= LIMIT && !text.starts_with(r"\\?\") => (),
+        _ => return path.to_owned(),
+    }
+
+    let normalized = normalize(path);
+    // Verbatim paths must use backslashes.
+    let text = match normalized.to_str() {
+        Some(text) => text.replace('/', r"\"),
+        None       => return path.to_owned(),
+    };
+    if text.starts_with(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", &text[2..]))
+    } else {
+        PathBuf::from(format!(r"\\?\{}", text))
+    }
+}
+
+/// Resolves `.` and `..` components lexically, dropping repeated
+/// separators.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir    => (),
+            Component::ParentDir => { normalized.pop(); }
+            _                    => normalized.push(component.as_os_str()),
+        }
+    }
+    normalized
+}
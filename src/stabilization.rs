@@ -0,0 +1,169 @@
+use super::{version, Probe, SyntaxFeature, Version};
+
+/// Well-known std items and language features, with the minor version of
+/// the Rust 1.x release that stabilized each. Language features go by their
+/// [`SyntaxFeature`] names where there is one.
+const STABILIZATIONS: &'static [(&'static str, u64)] = &[
+    // Language features.
+    ("question_mark",             13),
+    ("impl_trait",                26),
+    ("i128",                      26),
+    ("dyn_keyword",               27),
+    ("repr_transparent",          28),
+    ("raw_identifiers",           30),
+    ("const_fn",                  31),
+    ("impl_future_return",        36),
+    ("async_fn",                  39),
+    ("async_blocks",              39),
+    ("non_exhaustive",            40),
+    ("min_const_generics",        51),
+    ("generic_associated_types",  65),
+    ("label_break_value",         65),
+    ("let_else",                  65),
+    ("impl_trait_in_trait",       75),
+    ("async_fn_in_trait",         75),
+    ("c_str_literals",            77),
+    ("associated_type_bounds",    79),
+    ("inline_const",              79),
+    ("async_closures",            85),
+    ("if_let_chains",             88),
+
+    // Standard library items.
+    ("std::ptr::NonNull",                  25),
+    ("std::fs::read",                      26),
+    ("std::fs::read_to_string",            26),
+    ("std::fs::write",                     26),
+    ("std::ops::RangeInclusive",           26),
+    ("std::process::id",                   26),
+    ("std::hint::unreachable_unchecked",   27),
+    ("std::alloc::GlobalAlloc",            28),
+    ("std::alloc::Layout",                 28),
+    ("std::iter::repeat_with",             28),
+    ("std::num::NonZeroU32",               28),
+    ("std::num::NonZeroUsize",             28),
+    ("std::pin::Pin",                      33),
+    ("std::convert::Infallible",           34),
+    ("std::convert::TryFrom",              34),
+    ("std::convert::TryInto",              34),
+    ("std::iter::from_fn",                 34),
+    ("std::iter::successors",              34),
+    ("std::sync::atomic::AtomicU64",       34),
+    ("std::future::Future",                36),
+    ("std::mem::MaybeUninit",              36),
+    ("std::task::Waker",                   36),
+    ("std::any::type_name",                38),
+    ("std::mem::take",                     40),
+    ("std::task::Wake",                    51),
+    ("std::ptr::addr_of",                  51),
+    ("std::ops::ControlFlow",              55),
+    ("std::collections::TryReserveError",  57),
+    ("std::process::ExitCode",             61),
+    ("std::array::from_fn",                63),
+    ("std::thread::scope",                 63),
+    ("core::ffi::CStr",                    64),
+    ("core::ffi::c_int",                   64),
+    ("std::backtrace::Backtrace",          65),
+    ("std::hint::black_box",               66),
+    ("std::os::fd::OwnedFd",               66),
+    ("std::cell::OnceCell",                70),
+    ("std::io::IsTerminal",                70),
+    ("std::sync::OnceLock",                70),
+    ("std::num::Saturating",               74),
+    ("std::num::NonZero",                  79),
+    ("std::path::absolute",                79),
+    ("std::cell::LazyCell",                80),
+    ("std::sync::LazyLock",                80),
+    ("core::error::Error",                 81),
+];
+
+impl Probe {
+    /// Looks up the Rust release that stabilized a well-known std item or
+    /// language feature, without running `rustc`.
+    ///
+    /// Items are named by their full path, such as `"std::convert::TryFrom"`,
+    /// and language features by their [`SyntaxFeature`] name, such as
+    /// `"let_else"`, or otherwise by the name of their feature gate. Names
+    /// not in the bundled table give `None`.
+    ///
+    /// Requires the `stabilization-db` feature.
+    ///
+    /// [`SyntaxFeature`]: enum.SyntaxFeature.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, Version};
+    ///
+    /// let probe = Probe::new();
+    /// assert_eq!( probe.stabilized_in("std::convert::TryFrom"), Some(Version::new(1, 34, 0)) );
+    /// assert_eq!( probe.stabilized_in("std::no::Such"),         None );
+    /// ```
+    pub fn stabilized_in(&self, name: &str) -> Option<Version> {
+        lookup(name)
+    }
+
+    /// Probes for a std item or language feature, answering from
+    /// [`stabilized_in`](#method.stabilized_in) and the compiler's version
+    /// when it can.
+    ///
+    /// Only `rustc -vV` is run for names in the bundled table, and its output
+    /// is cached. Names that aren't in the table, and pre-releases of the
+    /// version that stabilized the name, are probed live instead: a
+    /// [`SyntaxFeature`] name with [`probe_syntax`](#method.probe_syntax),
+    /// and anything else as a path in a `use` declaration.
+    ///
+    /// Requires the `stabilization-db` feature.
+    ///
+    /// [`SyntaxFeature`]: enum.SyntaxFeature.html
+    ///
+    /// # Panics
+    ///
+    /// If the child `rustc` cannot be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!(   probe.probe_stabilized("std::convert::TryFrom") );
+    /// assert!(   probe.probe_stabilized("std::collections::HashMap") );
+    /// assert!( ! probe.probe_stabilized("std::no::Such") );
+    /// ```
+    pub fn probe_stabilized(&self, name: &str) -> bool {
+        if let Some(stabilized) = self.stabilized_in(name) {
+            if let Some(present) = self.answer_from_version(stabilized) {
+                return present;
+            }
+        }
+
+        match SyntaxFeature::all().iter().find(|feature| feature.name() == name) {
+            Some(&feature) => self.probe_syntax(feature),
+            None           => self.probe_item(&format!("#[allow(unused_imports)] use {};", name)),
+        }
+    }
+
+    /// Decides whether something stabilized in the given version is present,
+    /// or `None` if the compiler's version doesn't settle it.
+    fn answer_from_version(&self, stabilized: Version) -> Option<bool> {
+        let verbose = try_opt!(self.rustc_verbose_version().ok());
+        let release = try_opt!(version::verbose_field(&verbose, "release"));
+        let current = try_opt!(Version::parse(release));
+
+        // A nightly or beta of the stabilizing release may predate it.
+        if current.major == stabilized.major && current.minor == stabilized.minor
+            && release.contains('-')
+        {
+            return None;
+        }
+
+        Some(current >= stabilized)
+    }
+}
+
+/// Looks up a name in the bundled table.
+pub fn lookup(name: &str) -> Option<Version> {
+    STABILIZATIONS.iter()
+        .find(|entry| entry.0 == name)
+        .map(|entry| Version::new(1, entry.1, 0))
+}
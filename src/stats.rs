@@ -0,0 +1,71 @@
+use std::fmt;
+use std::time::Duration;
+
+use compat;
+
+/// Counters and timings accumulated by a [`Probe`](struct.Probe.html).
+///
+/// Get these with [`Probe::stats`](struct.Probe.html#method.stats) to find
+/// out which probes make a build script slow. Clones of a probe share their
+/// statistics.
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::Probe;
+///
+/// let probe = Probe::new();
+/// probe.probe_type("u32");
+/// probe.probe_type("u512");
+///
+/// let stats = probe.stats();
+/// assert_eq!( stats.compilations, 2 );
+/// assert!( stats.slowest <= stats.compile_time );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProbeStats {
+    /// The number of probe programs handed to the backend.
+    pub compilations: u64,
+    /// How many of those compilations ended in an error, such as a timeout,
+    /// rather than an answer.
+    pub errors:       u64,
+    /// How many times cached information, such as the compiler version, was
+    /// used instead of running `rustc` again.
+    pub cache_hits:   u64,
+    /// The total wall time spent compiling.
+    pub compile_time: Duration,
+    /// The wall time of the slowest compilation.
+    pub slowest:      Duration,
+}
+
+/// Counts a compilation that took `elapsed`, and whether it ended in an
+/// error.
+pub fn record_compilation(stats: &mut ProbeStats, elapsed: Duration, error: bool) {
+    stats.compilations += 1;
+    if error {
+        stats.errors += 1;
+    }
+    stats.compile_time += elapsed;
+    if elapsed > stats.slowest {
+        stats.slowest = elapsed;
+    }
+}
+
+impl fmt::Display for ProbeStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} compilations ({} errors) in {}, slowest {}; {} cache hits",
+               self.compilations, self.errors,
+               Seconds(self.compile_time), Seconds(self.slowest),
+               self.cache_hits)
+    }
+}
+
+/// Formats a duration as seconds with millisecond precision.
+pub struct Seconds(pub Duration);
+
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{:03}s", self.0.as_secs(), compat::subsec_millis(self.0))
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use super::{Probe, ProbeReport, UnavailablePolicy};
+
+impl Probe {
+    /// Runs each of the named probe programs for each of the target
+    /// triples, so that assumptions can be checked across a whole support
+    /// matrix from one machine.
+    ///
+    /// The result maps each target to a report of its answers, or to `None`
+    /// if the target's standard library isn't installed, in which case the
+    /// rest of its probes are skipped. Install targets with
+    /// `rustup target add`. Each probe is run with a
+    /// [`named`](#method.named) copy of this probe, and problems are never
+    /// answered by the
+    /// [`on_probe_unavailable`](#method.on_probe_unavailable) policy.
+    ///
+    /// # Errors
+    ///
+    /// If the child `rustc` cannot be started or communicated with, or with
+    /// kind `InvalidInput` if `rustc` doesn't know one of the targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// let host = probe.host().unwrap();
+    /// let results = probe.probe_targets(&[&host, "thumbv6m-none-eabi"], &[
+    ///     ("atomic_u64", "fn main() { let _ = std::sync::atomic::AtomicU64::new(0); }"),
+    /// ]).unwrap();
+    ///
+    /// assert!( results[host.as_str()].is_some() );
+    /// if let Some(ref report) = results["thumbv6m-none-eabi"] {
+    ///     assert_eq!( report.get("atomic_u64"), Some(false) );
+    /// }
+    /// ```
+    pub fn probe_targets(&self, targets: &[&str], probes: &[(&str, &str)])
+                         -> io::Result<BTreeMap<String, Option<ProbeReport>>> {
+        let mut results = BTreeMap::new();
+
+        for &target in targets {
+            let mut probe = self.clone();
+            probe.target(target)
+                 .on_probe_unavailable(UnavailablePolicy::Panic);
+
+            let mut report = ProbeReport::new();
+            let mut installed = true;
+            for &(name, code) in probes {
+                match probe.named(name).probe_result(code) {
+                    Ok(present)                       => { report.record(name, present); }
+                    Err(ref e) if is_not_installed(e) => {
+                        installed = false;
+                        break;
+                    }
+                    Err(e)                            => return Err(e),
+                }
+            }
+
+            results.insert(target.to_owned(), if installed { Some(report) } else { None });
+        }
+
+        Ok(results)
+    }
+}
+
+/// Whether the error says that the target's standard library is missing,
+/// as `Probe::check_diagnostics` reports it.
+fn is_not_installed(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::NotFound
+        && error.to_string().contains("is not installed for target")
+}
@@ -0,0 +1,144 @@
+/// A language syntax feature that can be probed for with
+/// [`Probe::probe_syntax`](struct.Probe.html#method.probe_syntax).
+///
+/// Each feature knows the probe program that exercises it, the `cfg` name
+/// that [`Probe::emit_all_syntax_cfgs`](struct.Probe.html#method.emit_all_syntax_cfgs)
+/// emits for it, and the earliest edition in which the syntax is available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SyntaxFeature {
+    /// `let PAT = EXPR else { ... };`
+    LetElse,
+    /// `if let PAT = EXPR && COND { ... }`
+    IfLetChains,
+    /// `dyn Trait` trait-object syntax.
+    DynKeyword,
+    /// `async fn` declarations.
+    AsyncFn,
+    /// Raw identifiers such as `r#match`.
+    RawIdentifiers,
+    /// `break 'label value` out of a labeled block.
+    LabelBreakValue,
+    /// C string literals such as `c"hello"`.
+    CStrLiterals,
+    /// Generic associated types, such as `type Item<'a> where Self: 'a;`.
+    GenericAssociatedTypes,
+    /// Bounds on associated types in bounds, such as
+    /// `T: Iterator<Item: Clone>`.
+    AssociatedTypeBounds,
+    /// `impl Trait` as the value of an associated type, such as
+    /// `type Iter = impl Iterator<Item = u8>;`.
+    ImplTraitInAssocType,
+    /// `async { ... }` blocks.
+    AsyncBlocks,
+    /// Async closures such as `async || { ... }`.
+    AsyncClosures,
+    /// `async fn` in traits and their impls.
+    AsyncFnInTrait,
+    /// Functions returning `impl Future<Output = T>`.
+    ImplFutureReturn,
+}
+
+static ALL_SYNTAX_FEATURES: [SyntaxFeature; 14] = [
+    SyntaxFeature::LetElse,
+    SyntaxFeature::IfLetChains,
+    SyntaxFeature::DynKeyword,
+    SyntaxFeature::AsyncFn,
+    SyntaxFeature::RawIdentifiers,
+    SyntaxFeature::LabelBreakValue,
+    SyntaxFeature::CStrLiterals,
+    SyntaxFeature::GenericAssociatedTypes,
+    SyntaxFeature::AssociatedTypeBounds,
+    SyntaxFeature::ImplTraitInAssocType,
+    SyntaxFeature::AsyncBlocks,
+    SyntaxFeature::AsyncClosures,
+    SyntaxFeature::AsyncFnInTrait,
+    SyntaxFeature::ImplFutureReturn,
+];
+
+impl SyntaxFeature {
+    /// All known syntax features, in declaration order.
+    pub fn all() -> &'static [SyntaxFeature] {
+        &ALL_SYNTAX_FEATURES
+    }
+
+    /// The `cfg` name used for this feature, such as `"let_else"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SyntaxFeature::LetElse                => "let_else",
+            SyntaxFeature::IfLetChains            => "if_let_chains",
+            SyntaxFeature::DynKeyword             => "dyn_keyword",
+            SyntaxFeature::AsyncFn                => "async_fn",
+            SyntaxFeature::RawIdentifiers         => "raw_identifiers",
+            SyntaxFeature::LabelBreakValue        => "label_break_value",
+            SyntaxFeature::CStrLiterals           => "c_str_literals",
+            SyntaxFeature::GenericAssociatedTypes => "generic_associated_types",
+            SyntaxFeature::AssociatedTypeBounds   => "associated_type_bounds",
+            SyntaxFeature::ImplTraitInAssocType   => "impl_trait_in_assoc_type",
+            SyntaxFeature::AsyncBlocks            => "async_blocks",
+            SyntaxFeature::AsyncClosures          => "async_closures",
+            SyntaxFeature::AsyncFnInTrait         => "async_fn_in_trait",
+            SyntaxFeature::ImplFutureReturn       => "impl_future_return",
+        }
+    }
+
+    /// The edition the probe program must be compiled with, if the syntax
+    /// is not available in the 2015 edition.
+    pub fn edition(self) -> Option<&'static str> {
+        match self {
+            SyntaxFeature::AsyncFn        => Some("2018"),
+            SyntaxFeature::AsyncBlocks    => Some("2018"),
+            SyntaxFeature::AsyncClosures  => Some("2018"),
+            SyntaxFeature::AsyncFnInTrait => Some("2018"),
+            SyntaxFeature::CStrLiterals   => Some("2021"),
+            SyntaxFeature::IfLetChains    => Some("2024"),
+            _                             => None,
+        }
+    }
+
+    /// The whole program used to probe for this feature.
+    pub fn code(self) -> &'static str {
+        match self {
+            SyntaxFeature::LetElse =>
+                "fn main() { let Some(x) = Some(1) else { return }; let _ = x; }",
+            SyntaxFeature::IfLetChains =>
+                "fn main() { let a = Some(1); if let Some(x) = a && x > 0 { } }",
+            SyntaxFeature::DynKeyword =>
+                "use std::fmt::Debug; fn main() { let x: &dyn Debug = &1; let _ = x; }",
+            SyntaxFeature::AsyncFn =>
+                "async fn f() { } fn main() { let _ = f(); }",
+            SyntaxFeature::RawIdentifiers =>
+                "fn r#match() { } fn main() { r#match(); }",
+            SyntaxFeature::LabelBreakValue =>
+                "fn main() { let x = 'a: { break 'a 1 }; let _ = x; }",
+            SyntaxFeature::CStrLiterals =>
+                "fn main() { let s = c\"hello\"; let _ = s; }",
+            SyntaxFeature::GenericAssociatedTypes =>
+                "trait Lend { type Item<'a> where Self: 'a; } \
+                 impl Lend for () { type Item<'a> = &'a (); } fn main() { }",
+            SyntaxFeature::AssociatedTypeBounds =>
+                "fn f<I: Iterator<Item: Clone>>(_: I) { } fn main() { f(0..1); }",
+            SyntaxFeature::ImplTraitInAssocType =>
+                "trait Make { type Out; fn make(&self) -> Self::Out; } \
+                 impl Make for () { type Out = impl Sized; fn make(&self) -> Self::Out { 0 } } \
+                 fn main() { let _ = ().make(); }",
+            SyntaxFeature::AsyncBlocks =>
+                "fn main() { let _ = async { 1 }; }",
+            SyntaxFeature::AsyncClosures =>
+                "fn main() { let f = async || 1; let _ = f(); }",
+            SyntaxFeature::AsyncFnInTrait =>
+                "trait Fetch { async fn fetch(&self) -> u8; } \
+                 impl Fetch for () { async fn fetch(&self) -> u8 { 0 } } \
+                 fn main() { let _ = ().fetch(); }",
+            SyntaxFeature::ImplFutureReturn =>
+                "use std::future::Future; use std::pin::Pin; use std::task::{Context, Poll}; \
+                 struct Ready; \
+                 impl Future for Ready { \
+                     type Output = u8; \
+                     fn poll(self: Pin<&mut Self>, _: &mut Context) -> Poll<u8> { Poll::Ready(0) } \
+                 } \
+                 fn ready() -> impl Future<Output = u8> { Ready } \
+                 fn main() { let _ = ready(); }",
+        }
+    }
+}
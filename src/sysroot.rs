@@ -0,0 +1,60 @@
+use std::io;
+use std::path::PathBuf;
+
+use super::Probe;
+
+impl Probe {
+    /// Determines the sysroot of the compiler, the directory holding its
+    /// standard library and other components.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc --print sysroot` cannot be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// assert!( probe.sysroot().unwrap().is_dir() );
+    /// ```
+    pub fn sysroot(&self) -> io::Result<PathBuf> {
+        let output = self.print_info("sysroot")?;
+        Ok(PathBuf::from(output.trim()))
+    }
+
+    /// Determines whether the standard library can be built from source, as
+    /// with Cargo's `-Z build-std`, rather than using a prebuilt one for the
+    /// target.
+    ///
+    /// This needs a nightly toolchain, or `RUSTC_BOOTSTRAP=1`, to accept
+    /// `-Z` flags, and the standard library's sources, which rustup installs
+    /// as the `rust-src` component. Whether the target's standard library
+    /// builds is not checked.
+    ///
+    /// # Errors
+    ///
+    /// If `rustc -vV` or `rustc --print sysroot` cannot be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if probe.can_build_std().unwrap() {
+    ///     println!("cargo:rustc-cfg=build_std");
+    /// }
+    /// ```
+    pub fn can_build_std(&self) -> io::Result<bool> {
+        if !self.accepts_unstable()? {
+            return Ok(false);
+        }
+
+        // The sources moved from `src` to `library` in Rust 1.47.
+        let src = self.sysroot()?.join("lib").join("rustlib").join("src").join("rust");
+        Ok(src.join("library").join("std").join("Cargo.toml").is_file()
+           || src.join("src").join("libstd").join("Cargo.toml").is_file())
+    }
+}
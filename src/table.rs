@@ -0,0 +1,137 @@
+use super::ProbeReport;
+
+/// A table of which features are supported where, rendered as Markdown or
+/// HTML.
+///
+/// Each column is a [`ProbeReport`](struct.ProbeReport.html) from one
+/// toolchain or target, and each row is a probe name, in the order the names
+/// first appear. Cells are `✓` for probes that succeeded, `✗` for probes that
+/// failed, and empty for probes that a column doesn't have.
+///
+/// # Examples
+///
+/// ```
+/// use feature_probe::{ProbeReport, SupportTable};
+///
+/// let mut stable = ProbeReport::new();
+/// stable.record("i128", true);
+/// stable.record("never_type", false);
+///
+/// let mut old = ProbeReport::new();
+/// old.record("i128", false);
+///
+/// let mut table = SupportTable::new();
+/// table.column("stable", &stable).column("1.25.0", &old);
+///
+/// assert_eq!( table.to_markdown(),
+///             "| Feature | stable | 1.25.0 |\n\
+///              |---|:-:|:-:|\n\
+///              | `i128` | ✓ | ✗ |\n\
+///              | `never_type` | ✗ |  |\n" );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SupportTable {
+    columns: Vec<(String, ProbeReport)>,
+}
+
+impl SupportTable {
+    /// Creates a table with no columns.
+    pub fn new() -> Self {
+        SupportTable {
+            columns: Vec::new(),
+        }
+    }
+
+    /// Adds a column with the given heading, such as a toolchain or a
+    /// target triple, holding the results in the report.
+    pub fn column<S: Into<String>>(&mut self, heading: S, report: &ProbeReport) -> &mut Self {
+        self.columns.push((heading.into(), report.clone()));
+        self
+    }
+
+    /// Renders the table as GitHub-flavored Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("| Feature |");
+        for column in &self.columns {
+            out.push_str(&format!(" {} |", column.0.replace('|', "\\|")));
+        }
+        out.push_str("\n|---|");
+        for _ in &self.columns {
+            out.push_str(":-:|");
+        }
+        out.push('\n');
+
+        for name in self.names() {
+            out.push_str(&format!("| `{}` |", name.replace('|', "\\|")));
+            for column in &self.columns {
+                out.push_str(&format!(" {} |", cell(column.1.get(&name))));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Renders the table as an HTML `<table>` element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{ProbeReport, SupportTable};
+    ///
+    /// let mut report = ProbeReport::new();
+    /// report.record("i128", true);
+    ///
+    /// let html = SupportTable::new().column("x86_64-unknown-linux-gnu", &report).to_html();
+    /// assert!( html.contains("<th>x86_64-unknown-linux-gnu</th>") );
+    /// assert!( html.contains("<td><code>i128</code></td><td>✓</td>") );
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut out = String::from("<table>\n<thead>\n<tr><th>Feature</th>");
+        for column in &self.columns {
+            out.push_str(&format!("<th>{}</th>", escape_html(&column.0)));
+        }
+        out.push_str("</tr>\n</thead>\n<tbody>\n");
+
+        for name in self.names() {
+            out.push_str(&format!("<tr><td><code>{}</code></td>", escape_html(&name)));
+            for column in &self.columns {
+                out.push_str(&format!("<td>{}</td>", cell(column.1.get(&name))));
+            }
+            out.push_str("</tr>\n");
+        }
+
+        out.push_str("</tbody>\n</table>\n");
+        out
+    }
+
+    /// Every probe name in any column, in the order they first appear.
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+
+        for column in &self.columns {
+            for (name, _) in &column.1 {
+                if !names.iter().any(|known| known == name) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+
+        names
+    }
+}
+
+fn cell(result: Option<bool>) -> &'static str {
+    match result {
+        Some(true)  => "✓",
+        Some(false) => "✗",
+        None        => "",
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
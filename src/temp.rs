@@ -0,0 +1,58 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+#[allow(deprecated)]
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+use compat;
+
+// `AtomicUsize::new` would require Rust 1.24.
+#[allow(deprecated)]
+static COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// A fresh, empty directory that is removed, along with its contents, when
+/// dropped.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Creates a new directory in the system temporary directory.
+    pub fn new() -> io::Result<Self> {
+        TempDir::new_in(&env::temp_dir())
+    }
+
+    /// Creates a new directory in the given directory, creating that too if
+    /// need be.
+    pub fn new_in(parent: &Path) -> io::Result<Self> {
+        fs::create_dir_all(parent)?;
+
+        loop {
+            let path = parent.join(unique_name("feature-probe"));
+
+            match fs::create_dir(&path) {
+                Ok(())                                                 => return Ok(TempDir { path: path }),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e)                                                 => return Err(e),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// A name that is unique within the process and among processes, made of
+/// the prefix, the process ID and a counter.
+pub fn unique_name(prefix: &str) -> String {
+    format!("{}_{}_{}", prefix, compat::process_id(), COUNTER.fetch_add(1, Ordering::SeqCst))
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,27 @@
+use std::char;
+
+/// Decodes the output of a compiler or other child process.
+///
+/// Output is expected to be UTF-8, but on Windows with a non-English locale,
+/// tools such as the linker may write in the console's code page, or in
+/// UTF-16. Invalid sequences become U+FFFD, so that the ASCII parts that
+/// diagnostics are recognized by survive, and output with NUL bytes and a
+/// byte order mark or an even length is taken as UTF-16LE, which compiler
+/// text otherwise never contains.
+pub fn decode(bytes: &[u8]) -> String {
+    if bytes.starts_with(b"\xEF\xBB\xBF") {
+        return String::from_utf8_lossy(&bytes[3..]).into_owned();
+    }
+
+    let bom = bytes.starts_with(b"\xFF\xFE");
+    if bom || (bytes.len() % 2 == 0 && bytes.contains(&0)) {
+        let units: Vec<u16> = bytes[if bom { 2 } else { 0 }..].chunks(2)
+            .map(|pair| u16::from(pair[0]) | u16::from(*pair.get(1).unwrap_or(&0)) << 8)
+            .collect();
+        return char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+    }
+
+    String::from_utf8_lossy(bytes).into_owned()
+}
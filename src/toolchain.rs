@@ -0,0 +1,208 @@
+use std::env;
+use std::ffi::OsStr;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::{compat, shared, text, version, Probe, ToolchainPolicy};
+use super::shared::Namespace;
+
+/// The key under which the outcome of the toolchain check is shared.
+const TOOLCHAIN_CHECK: &'static str = "// feature-probe toolchain check";
+
+impl Probe {
+    /// Checks that probes are compiled by the toolchain the crate itself is
+    /// built with, since answers from another compiler may not hold for it.
+    ///
+    /// Two things are compared against the probe's `rustc -vV`:
+    ///
+    ///   - the `rustc` next to the `cargo` running the build (`CARGO`), and
+    ///   - the toolchain pinned by `RUSTUP_TOOLCHAIN`, or by a
+    ///     `rust-toolchain.toml` or `rust-toolchain` file above the crate,
+    ///     when it names a version or a channel.
+    ///
+    /// # Errors
+    ///
+    /// With kind `InvalidData` if either disagrees, or if `rustc` cannot be
+    /// run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Probe;
+    ///
+    /// let probe = Probe::new();
+    /// if let Err(e) = probe.check_toolchain() {
+    ///     println!("cargo:warning={}", e);
+    /// }
+    /// ```
+    pub fn check_toolchain(&self) -> io::Result<()> {
+        let verbose = self.rustc_verbose_version()?;
+        let release = version::verbose_field(&verbose, "release").unwrap_or("");
+
+        if let Some((cargo_rustc, expected)) = cargo_rustc_verbose() {
+            let fields = ["release", "commit-hash"];
+            if fields.iter().any(|field| version::verbose_field(&verbose, field)
+                                          != version::verbose_field(&expected, field)) {
+                return Err(mismatch(&self.rustc, release,
+                                    &format!("cargo's rustc {} is {}",
+                                             cargo_rustc.display(),
+                                             version::verbose_field(&expected, "release")
+                                                 .unwrap_or("unknown"))));
+            }
+        }
+
+        if let Some((channel, source)) = pinned_toolchain() {
+            if channel_matches(&channel, release) == Some(false) {
+                return Err(mismatch(&self.rustc, release,
+                                    &format!("{} pins {}", source, channel)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Chooses what to do when probes would be compiled by a different
+    /// toolchain than the crate, as found by
+    /// [`check_toolchain`](#method.check_toolchain) before the first
+    /// compilation of each configuration. The default is to warn;
+    /// [`toolchain`](#method.toolchain) turns the check off, since it picks
+    /// a different toolchain on purpose.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ToolchainPolicy};
+    ///
+    /// let mut probe = Probe::new();
+    /// probe.on_toolchain_mismatch(ToolchainPolicy::Error);
+    /// assert!( probe.probe_type("u32") );
+    /// ```
+    pub fn on_toolchain_mismatch(&mut self, policy: ToolchainPolicy) -> &mut Self {
+        self.mismatch = policy;
+        self
+    }
+}
+
+/// Applies the toolchain mismatch policy. Each configuration is checked
+/// once per process, and warned about at most once.
+pub fn guard(probe: &Probe) -> io::Result<()> {
+    if probe.mismatch == ToolchainPolicy::Ignore {
+        return Ok(());
+    }
+
+    let fingerprint = match probe.fingerprint() {
+        Ok(fingerprint) => fingerprint,
+        // The compilation will report the problem.
+        Err(_)          => return Ok(()),
+    };
+    let first = match shared::get(Namespace::Internal, fingerprint, TOOLCHAIN_CHECK) {
+        Some(true)  => return Ok(()),
+        Some(false) => false,
+        None        => true,
+    };
+    if !first && probe.mismatch == ToolchainPolicy::Warn {
+        return Ok(());
+    }
+
+    let result = match probe.check_toolchain() {
+        Err(ref e) if e.kind() != io::ErrorKind::InvalidData => return Ok(()),
+        result                                               => result,
+    };
+    shared::insert(Namespace::Internal, fingerprint, TOOLCHAIN_CHECK, result.is_ok());
+
+    match (result, probe.mismatch) {
+        (Err(e), ToolchainPolicy::Error) => Err(e),
+        (Err(e), _)                      => {
+            probe.emit_warning(&format!("feature-probe: {}", e));
+            Ok(())
+        }
+        (Ok(()), _)                      => Ok(()),
+    }
+}
+
+fn mismatch(rustc: &OsStr, release: &str, other: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,
+                   format!("probes are compiled by {:?} ({}), but {}; set RUSTC or the \
+                            toolchain so that they agree",
+                           rustc, release, other))
+}
+
+/// The `rustc` next to the `cargo` running the build, and its `-vV` output.
+fn cargo_rustc_verbose() -> Option<(PathBuf, String)> {
+    let cargo = PathBuf::from(try_opt!(env::var_os("CARGO")));
+    let rustc = cargo.with_file_name(format!("rustc{}", env::consts::EXE_SUFFIX));
+    if !rustc.is_file() {
+        return None;
+    }
+
+    let output = try_opt!(Command::new(&rustc).arg("-vV").output().ok());
+    if !output.status.success() {
+        return None;
+    }
+    Some((rustc, text::decode(&output.stdout)))
+}
+
+/// The toolchain rustup would pick for the crate, and where it comes from.
+fn pinned_toolchain() -> Option<(String, String)> {
+    if let Some(toolchain) = env::var_os("RUSTUP_TOOLCHAIN") {
+        return Some((toolchain.to_string_lossy().into_owned(),
+                     String::from("RUSTUP_TOOLCHAIN")));
+    }
+
+    let start = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None      => try_opt!(env::current_dir().ok()),
+    };
+    for dir in compat::ancestors(&start) {
+        for name in &["rust-toolchain.toml", "rust-toolchain"] {
+            let path = dir.join(name);
+            if let Ok(contents) = compat::read_to_string(&path) {
+                return toolchain_file_channel(&contents)
+                    .map(|channel| (channel, path.display().to_string()));
+            }
+        }
+    }
+    None
+}
+
+/// The channel named by a toolchain file, in either the TOML format or the
+/// legacy one-line format.
+fn toolchain_file_channel(contents: &str) -> Option<String> {
+    for line in contents.lines() {
+        let mut parts = line.splitn(2, '=');
+        if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            if key.trim() == "channel" {
+                return Some(value.trim().trim_matches(|c| c == '"' || c == '\'').to_owned());
+            }
+        }
+    }
+
+    let mut lines = contents.lines().map(str::trim).filter(|line| !line.is_empty());
+    match (lines.next(), lines.next()) {
+        (Some(line), None) if !line.contains('[') && !line.contains('=') => Some(line.to_owned()),
+        _                                                                => None,
+    }
+}
+
+/// Whether a compiler release, such as `1.67.1` or `1.80.0-nightly`, is
+/// from the given channel, or `None` if the channel is a custom toolchain
+/// that can't be judged.
+fn channel_matches(channel: &str, release: &str) -> Option<bool> {
+    let prerelease = release.find('-').map(|i| &release[i + 1..]);
+
+    if channel.starts_with("nightly") {
+        Some(prerelease.map_or(false, |pre| pre.starts_with("nightly")))
+    } else if channel.starts_with("beta") {
+        Some(prerelease.map_or(false, |pre| pre.starts_with("beta")))
+    } else if channel.starts_with("stable") {
+        Some(prerelease.is_none())
+    } else if channel.starts_with(|c: char| c.is_digit(10)) {
+        // A version, perhaps followed by the host, such as `1.67-x86_64-…`.
+        let version = channel.split('-').next().unwrap_or(channel);
+        let number = release.split('-').next().unwrap_or(release);
+        Some(number == version || number.starts_with(&format!("{}.", version)))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,74 @@
+use super::Probe;
+
+/// How far an API can be used, from
+/// [`Probe::probe_usable`](struct.Probe.html#method.probe_usable).
+///
+/// Each level includes the ones before it, so levels can be compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Usability {
+    /// The API doesn't exist.
+    Missing,
+    /// The API exists, but can't be used the way the crate needs, such as
+    /// when it is unstable or lacks a needed trait implementation.
+    Exists,
+    /// The API exists and can be used the way the crate needs.
+    Usable,
+}
+
+impl Probe {
+    /// Distinguishes an API that exists from one that can be used the way
+    /// the crate needs, by running the `exists` probe and then, if it
+    /// succeeds, the `behavior` probe.
+    ///
+    /// This emits `cargo:rustc-cfg=NAME_exists` through this probe's
+    /// [`reporter`](#method.reporter) if the API exists, and also
+    /// `cargo:rustc-cfg=NAME` if it is usable, with the names prefixed by any
+    /// [`cfg_prefix`](#method.cfg_prefix). The probes are given
+    /// [`named`](#method.named) copies of this probe, with the same names.
+    ///
+    /// # Panics
+    ///
+    /// If either probe panics, as probes do when the child `rustc` cannot
+    /// be started or communicated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, RecordingReporter, Usability};
+    ///
+    /// let recorder = RecordingReporter::new();
+    /// let mut probe = Probe::new();
+    /// probe.reporter(recorder.clone());
+    ///
+    /// let usability = probe.probe_usable("i128_add",
+    ///                                    |probe| probe.probe_type("i128"),
+    ///                                    |probe| probe.probe_expression("1i128 + 2"));
+    /// assert_eq!( usability, Usability::Usable );
+    ///
+    /// let usability = probe.probe_usable("i128_add_bool",
+    ///                                    |probe| probe.probe_type("i128"),
+    ///                                    |probe| probe.probe_expression("1i128 + true"));
+    /// assert_eq!( usability, Usability::Exists );
+    ///
+    /// assert_eq!( recorder.cfgs(),
+    ///             vec!["i128_add_exists", "i128_add", "i128_add_bool_exists"] );
+    /// ```
+    pub fn probe_usable<E, B>(&self, name: &str, exists: E, behavior: B) -> Usability
+        where E: FnOnce(&Probe) -> bool,
+              B: FnOnce(&Probe) -> bool
+    {
+        let exists_name = format!("{}_exists", name);
+        if !exists(&self.named(exists_name.as_str())) {
+            return Usability::Missing;
+        }
+        self.emit_cfg(&exists_name);
+
+        if !behavior(&self.named(name)) {
+            return Usability::Exists;
+        }
+        self.emit_cfg(name);
+
+        Usability::Usable
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+/// A Rust release version, such as 1.34.0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Version {
+    /// The major version, which is 1 for every Rust release so far.
+    pub major: u64,
+    /// The minor version.
+    pub minor: u64,
+    /// The patch version.
+    pub patch: u64,
+}
+
+impl Version {
+    /// Creates a version from its components.
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version { major: major, minor: minor, patch: patch }
+    }
+
+    /// Parses a version such as `"1.34.0"`, ignoring any pre-release suffix
+    /// such as `-nightly`. A missing patch component is taken to be zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::Version;
+    ///
+    /// assert_eq!( Version::parse("1.31.0-nightly"), Some(Version::new(1, 31, 0)) );
+    /// assert_eq!( Version::parse("1.56"),           Some(Version::new(1, 56, 0)) );
+    /// assert_eq!( Version::parse("one.two"),        None );
+    /// ```
+    pub fn parse(s: &str) -> Option<Self> {
+        let numbers = s.trim().split(|c| c == '-' || c == '+').next().unwrap_or("");
+        let mut parts = numbers.split('.');
+
+        let major = try_opt!(parts.next().and_then(|part| part.parse().ok()));
+        let minor = try_opt!(parts.next().and_then(|part| part.parse().ok()));
+        let patch = match parts.next() {
+            Some(part) => try_opt!(part.parse().ok()),
+            None       => 0,
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Version::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Looks up a `key: value` field in `rustc -vV` output.
+pub fn verbose_field<'a>(verbose: &'a str, key: &str) -> Option<&'a str> {
+    verbose.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(k), Some(v)) if k.trim() == key => Some(v.trim()),
+                _                                    => None,
+            }
+        })
+        .next()
+}
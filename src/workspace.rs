@@ -0,0 +1,195 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::{compat, temp, Probe, ProbeReport};
+
+/// The environment variable that overrides where shared workspace results
+/// are kept.
+const RESULTS_ENV: &'static str = "FEATURE_PROBE_WORKSPACE_RESULTS";
+
+impl Probe {
+    /// Runs the probe programs in a directory, as with
+    /// [`probe_dir`](#method.probe_dir), and shares the results with the rest
+    /// of the workspace, for a helper run once at the workspace root, such as
+    /// an `xtask`.
+    ///
+    /// The results are written to [`workspace_results_path`], where the build
+    /// scripts of member crates read them with
+    /// [`workspace_report`](#method.workspace_report) instead of each
+    /// compiling the same probes again.
+    ///
+    /// [`workspace_results_path`]: fn.workspace_results_path.html
+    ///
+    /// # Errors
+    ///
+    /// If the probes cannot be carried out or the results cannot be written.
+    pub fn share_workspace_probes<P: AsRef<Path>>(&self, dir: P) -> io::Result<ProbeReport> {
+        let report = self.probe_dir(dir)?;
+        self.write_workspace_report(&report)?;
+        Ok(report)
+    }
+
+    /// Shares an existing report with the rest of the workspace, as
+    /// [`share_workspace_probes`](#method.share_workspace_probes) does, and
+    /// returns the path written to.
+    ///
+    /// The results are stamped with the probe's
+    /// [`fingerprint`](#method.fingerprint), so that they are only reused by
+    /// probes that would have given the same answers.
+    ///
+    /// # Errors
+    ///
+    /// If a name in the report is empty or contains whitespace, which the
+    /// file has no way to represent (`ErrorKind::InvalidInput`), or the
+    /// target directory cannot be found, or `rustc -vV` cannot be run, or the
+    /// file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use feature_probe::{Probe, ProbeReport};
+    ///
+    /// let path = std::env::temp_dir().join(format!("fp-doc-workspace-{}", std::process::id()));
+    /// std::env::set_var("FEATURE_PROBE_WORKSPACE_RESULTS", &path);
+    ///
+    /// let probe = Probe::builder().edition("2018").build();
+    /// let mut report = ProbeReport::new();
+    /// report.record("i128", probe.probe_type("i128"));
+    /// assert_eq!( probe.write_workspace_report(&report).unwrap(), path );
+    ///
+    /// // Then, in a member's build script:
+    /// let shared = probe.workspace_report().unwrap().unwrap();
+    /// assert_eq!( shared.get("i128"), Some(true) );
+    ///
+    /// let other = Probe::builder().edition("2015").build();
+    /// assert_eq!( other.workspace_report().unwrap(), None );
+    ///
+    /// let mut spaced = ProbeReport::new();
+    /// spaced.record("two words", true);
+    /// assert!( probe.write_workspace_report(&spaced).is_err() );
+    ///
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn write_workspace_report(&self, report: &ProbeReport) -> io::Result<PathBuf> {
+        for (name, _) in report {
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          format!("probe name {:?} can't be shared", name)));
+            }
+        }
+
+        let path = workspace_results_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut contents = format!("fingerprint {:016x}\n", self.fingerprint()?);
+        for (name, present) in report {
+            contents.push_str(&format!("{} {}\n", name, if present { 1 } else { 0 }));
+        }
+
+        // Replace the file atomically, since members may be reading it.
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(format!(".{}.tmp", temp::unique_name("workspace")));
+        File::create(&temp_path)?.write_all(contents.as_bytes())?;
+        fs::rename(&temp_path, &path)?;
+        Ok(path)
+    }
+
+    /// Reads the results shared by
+    /// [`share_workspace_probes`](#method.share_workspace_probes), or `None`
+    /// if there are none or they were produced by a probe with a different
+    /// [`fingerprint`](#method.fingerprint), in which case probe as usual.
+    ///
+    /// Configure the probe the same way on both sides. In particular,
+    /// [`new`](#method.new) picks up the edition of the crate whose build
+    /// script is running, so set the [`edition`](#method.edition)
+    /// explicitly. The reporter is told to rerun the build script when the
+    /// results change.
+    ///
+    /// # Errors
+    ///
+    /// If the target directory cannot be found, or `rustc -vV` cannot be run,
+    /// or the file exists but cannot be read.
+    pub fn workspace_report(&self) -> io::Result<Option<ProbeReport>> {
+        let path = workspace_results_path()?;
+        self.reporter.rerun_if_env_changed(RESULTS_ENV);
+        self.reporter.rerun_if_changed(&path);
+
+        let mut contents = String::new();
+        match File::open(&path) {
+            Ok(mut file)                                      => { file.read_to_string(&mut contents)?; }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e)                                            => return Err(e),
+        }
+
+        let stamp = format!("fingerprint {:016x}", self.fingerprint()?);
+        let mut lines = contents.lines();
+        if lines.next() != Some(stamp.as_str()) {
+            return Ok(None);
+        }
+
+        let mut report = ProbeReport::new();
+        for line in lines {
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some(name), Some("1"), None) => { report.record(name, true); }
+                (Some(name), Some("0"), None) => { report.record(name, false); }
+                _                             => (),
+            }
+        }
+
+        Ok(Some(report))
+    }
+}
+
+/// The file where results shared across a workspace are kept:
+/// `feature-probe/workspace-results` in Cargo's target directory.
+///
+/// The target directory is taken from `CARGO_TARGET_DIR` if set, and
+/// otherwise found above `OUT_DIR` in a build script, or as `target` in the
+/// workspace root elsewhere. Setting `FEATURE_PROBE_WORKSPACE_RESULTS`
+/// overrides the whole path.
+///
+/// # Errors
+///
+/// If the target directory cannot be found.
+pub fn workspace_results_path() -> io::Result<PathBuf> {
+    if let Some(path) = env::var_os(RESULTS_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+
+    target_dir()
+        .map(|dir| dir.join("feature-probe").join("workspace-results"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound,
+                                      "cannot find Cargo's target directory"))
+}
+
+fn target_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("CARGO_TARGET_DIR") {
+        let dir = PathBuf::from(dir);
+        return Some(if dir.is_relative() { try_opt!(env::current_dir().ok()).join(dir) } else { dir });
+    }
+
+    // In a build script, `OUT_DIR` is TARGET/[TRIPLE/]PROFILE/build/PKG/out,
+    // and Cargo marks TARGET with a `CACHEDIR.TAG`.
+    if let Some(out_dir) = env::var_os("OUT_DIR") {
+        let out_dir = PathBuf::from(out_dir);
+        if let Some(dir) = compat::ancestors(&out_dir).find(|dir| dir.join("CACHEDIR.TAG").is_file()) {
+            return Some(dir.to_owned());
+        }
+        return compat::ancestors(&out_dir).nth(4).map(Path::to_owned);
+    }
+
+    let start = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None      => try_opt!(env::current_dir().ok()),
+    };
+    let root = compat::ancestors(&start)
+        .find(|dir| compat::read_to_string(dir.join("Cargo.toml")).ok()
+                .map_or(false, |manifest| manifest.lines().any(|line| line.trim() == "[workspace]")))
+        .unwrap_or(&start);
+    Some(root.join("target"))
+}